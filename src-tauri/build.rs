@@ -34,15 +34,50 @@ enum AppBind {
     Multiple(Vec<String>),
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum KeySteps {
+    Single(String),
+    Sequence(Vec<String>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum KeyValue {
+    Common(KeySteps),
+    PerOs {
+        #[serde(default)]
+        windows: Option<KeySteps>,
+        #[serde(default)]
+        macos: Option<KeySteps>,
+        #[serde(default)]
+        linux: Option<KeySteps>,
+    },
+}
+
 #[derive(Deserialize)]
 struct Keybinding {
     #[allow(dead_code)]
     action: String,
     #[allow(dead_code)]
-    key: String,
+    key: KeyValue,
     #[serde(default)]
     #[allow(dead_code)]
     tags: Vec<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    title_contains: Option<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    description: Option<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    category: Option<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    icon: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -52,9 +87,11 @@ enum OsType {
     Windows,
     #[serde(rename = "macos")]
     MacOS,
+    Linux,
 }
 
 #[derive(Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // keybindings.json の各項目をそのまま対応させるため
 struct AppConfig {
     #[serde(default)]
     #[allow(dead_code)]
@@ -71,6 +108,30 @@ struct AppConfig {
     #[serde(default)]
     #[allow(dead_code)]
     keybindings: Vec<Keybinding>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    auto_icon: bool,
+    #[serde(default)]
+    #[allow(dead_code)]
+    global: bool,
+    #[serde(default)]
+    #[allow(dead_code)]
+    color: Option<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    url: Option<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    fallback: bool,
+    #[serde(default)]
+    #[allow(dead_code)]
+    disabled: bool,
+    #[serde(default)]
+    #[allow(dead_code)]
+    match_priority: i32,
+    #[serde(default)]
+    #[allow(dead_code)]
+    launch: Option<String>,
 }
 
 // ============================================================
@@ -80,16 +141,22 @@ struct AppConfig {
 fn main() {
     // ターゲットOSを取得
     let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
-    let platform = if target_os == "macos" { "macos" } else { "windows" };
+    let platform = match target_os.as_str() {
+        "macos" => "macos",
+        "linux" => "linux",
+        _ => "windows",
+    };
 
     // プラットフォーム別のdefaults設定を検証
     validate_defaults(platform);
 
-    // 両方のプラットフォームのdefaultsファイルが変更されたら再ビルド
+    // 各プラットフォームのdefaultsファイルが変更されたら再ビルド
     println!("cargo:rerun-if-changed=defaults/windows/settings.json");
     println!("cargo:rerun-if-changed=defaults/windows/keybindings.json");
     println!("cargo:rerun-if-changed=defaults/macos/settings.json");
     println!("cargo:rerun-if-changed=defaults/macos/keybindings.json");
+    println!("cargo:rerun-if-changed=defaults/linux/settings.json");
+    println!("cargo:rerun-if-changed=defaults/linux/keybindings.json");
 
     tauri_build::build();
 }
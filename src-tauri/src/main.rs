@@ -1,19 +1,30 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use chrono::Timelike;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Mutex, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::thread;
 use std::time::{Duration, SystemTime};
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Emitter, Manager, WebviewWindow, WindowEvent,
+    AppHandle, Emitter, Manager, RunEvent, WebviewWindow, WindowEvent,
 };
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 // バインド設定（文字列または配列）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,30 +44,139 @@ impl AppBind {
     }
 }
 
+// キー（文字列、または順次入力の各ステップの配列）
+// 文字列は "->" 区切りのほか、"g d" や "Ctrl+K Ctrl+S" のような空白区切りの順次入力も受け付ける
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeySteps {
+    Single(String),
+    Sequence(Vec<String>),
+}
+
+impl KeySteps {
+    /// "->" 区切りのキー文字列に変換
+    pub fn to_key_string(&self) -> String {
+        match self {
+            Self::Single(key) => normalize_key_sequence(key),
+            Self::Sequence(steps) => steps
+                .iter()
+                .map(|step| step.trim())
+                .collect::<Vec<_>>()
+                .join(" -> "),
+        }
+    }
+}
+
+// キーバインドのキー（全OS共通、またはOS別）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum KeyValue {
+    Common(KeySteps),
+    PerOs {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        windows: Option<KeySteps>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        macos: Option<KeySteps>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        linux: Option<KeySteps>,
+    },
+}
+
+impl KeyValue {
+    /// 指定したOSのキー（全OS共通の場合はそのまま、OS別の指定にそのOSがない場合は None）
+    pub const fn steps_for(&self, os: &OsType) -> Option<&KeySteps> {
+        match self {
+            Self::Common(steps) => Some(steps),
+            Self::PerOs {
+                windows,
+                macos,
+                linux,
+            } => match os {
+                OsType::Windows => windows.as_ref(),
+                OsType::MacOS => macos.as_ref(),
+                OsType::Linux => linux.as_ref(),
+            },
+        }
+    }
+
+    /// 指定したOSのキー文字列（"->" 区切り）
+    /// OS別の指定にそのOSがない場合と、"-"（未割り当て）と書かれている場合は None
+    pub fn resolve(&self, os: &OsType) -> Option<String> {
+        self.steps_for(os)
+            .map(KeySteps::to_key_string)
+            .filter(|key| key.trim() != "-")
+    }
+}
+
 // キーバインド設定
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keybinding {
     pub action: String,
-    pub key: String,
+    pub key: KeyValue,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// ウィンドウタイトルにこの文字列を含む場合のみ有効（例: "- NORMAL"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_contains: Option<String>,
+    /// 操作の詳しい説明（ツールチップや詳細表示用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 分類（例: "編集", "ナビゲーション"）。アプリ内でのグループ表示に使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// このキーバインド専用のアイコン（未設定の場合はアプリのアイコン）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
 }
 
-// OS種別（windows または macos のみ）
+impl Keybinding {
+    /// 指定したOSのキー文字列（"->" 区切り、未割り当ては None）
+    pub fn get_key(&self, os: &OsType) -> Option<String> {
+        self.key.resolve(os)
+    }
+}
+
+// OS種別（windows / macos / linux）
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum OsType {
     Windows,
     #[serde(rename = "macos")]
     MacOS,
+    Linux,
 }
 
 impl OsType {
+    /// すべてのOS種別
+    pub const ALL: [Self; 3] = [Self::Windows, Self::MacOS, Self::Linux];
+
+    /// 実行中のOS種別（Windows・macOS以外はLinuxとして扱う）
+    pub const fn current() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Windows
+        } else if cfg!(target_os = "macos") {
+            Self::MacOS
+        } else {
+            Self::Linux
+        }
+    }
+
     /// OS種別から表示名を取得
     pub const fn display_name(&self) -> &'static str {
         match self {
             Self::Windows => "Windows",
             Self::MacOS => "macOS",
+            Self::Linux => "Linux",
+        }
+    }
+
+    /// OS名（"windows" / "macos" / "linux"）からOS種別を取得
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "windows" => Some(Self::Windows),
+            "macos" => Some(Self::MacOS),
+            "linux" => Some(Self::Linux),
+            _ => None,
         }
     }
 
@@ -65,12 +185,14 @@ impl OsType {
         match self {
             Self::Windows => cfg!(target_os = "windows"),
             Self::MacOS => cfg!(target_os = "macos"),
+            Self::Linux => cfg!(target_os = "linux"),
         }
     }
 }
 
 // アプリ設定（統合形式）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // keybindings.json の各項目をそのまま対応させるため
 pub struct AppConfig {
     #[serde(default)]
     pub icon: Option<String>,
@@ -79,11 +201,42 @@ pub struct AppConfig {
     pub name: Option<String>,
     #[serde(default)]
     pub bind: Option<AppBind>,
-    /// OS種別（windows または macos）。指定時はnameとbindは不要
+    /// OS種別（windows / macos / linux）。指定時はnameとbindは不要
     #[serde(default)]
     pub os: Option<OsType>,
     #[serde(default)]
     pub keybindings: Vec<Keybinding>,
+    /// 実行ファイルからアイコンを自動取得する（Windowsのみ、失敗時はiconを使用）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_icon: bool,
+    /// アクティブウィンドウに関係なく常に表示する（アプリに紐付かない個人用ショートカット向け）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub global: bool,
+    /// アプリの色（CSSの色指定、オーバーレイの枠線に使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// 公式のショートカット一覧などのドキュメントURL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 他のアプリが1つもマッチしない場合にのみマッチする（汎用のショートカット向け）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fallback: bool,
+    /// このアプリを表示しない（`merge_defaults` 有効時にデフォルトのアプリを隠す用）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+    /// 複数のアプリがマッチした場合の優先度（大きいほど先、省略時は0）
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub match_priority: i32,
+    /// 起動していない場合の起動方法（実行ファイル・アプリのパス、macOS のアプリ名、Linux のデスクトップエントリ名）
+    /// シェルのコマンドとしては実行しない。パスの存在は起動時に確認する
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch: Option<String>,
+}
+
+/// 値が0かどうか（serdeの `skip_serializing_if` 用）
+#[allow(clippy::trivially_copy_pass_by_ref)] // serde が参照で渡すため
+const fn is_zero(value: &i32) -> bool {
+    *value == 0
 }
 
 impl AppConfig {
@@ -109,18 +262,84 @@ impl AppConfig {
     /// 現在のプラットフォームで有効かどうか
     /// osが指定されていない場合は常に有効、指定されている場合は一致時のみ有効
     pub fn is_available(&self) -> bool {
-        self.os.as_ref().is_none_or(OsType::is_current_platform)
+        !self.disabled && self.os.as_ref().is_none_or(OsType::is_current_platform)
+    }
+
+    /// 指定したプラットフォームで有効かどうか（他OSのプレビュー用）
+    pub fn is_available_on(&self, os: &OsType) -> bool {
+        !self.disabled && self.os.as_ref().is_none_or(|app_os| app_os == os)
+    }
+
+    /// 並び替え用のキー（先頭の絵文字・空白・記号を除いた小文字の表示名）
+    pub fn sort_key(&self) -> String {
+        self.get_name()
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
     }
 }
 
 // フロントエンドに渡す正規化されたショートカット
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub struct NormalizedShortcut {
     pub app: String,
     pub icon: String,
     pub action: String,
     pub key: String,
     pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// このプラットフォームではキーが割り当てられていない（設定のキーが "-"、keyは空文字）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unbound: bool,
+}
+
+// 検索スコア付きのショートカット（ハイライト表示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoredShortcut {
+    #[serde(flatten)]
+    pub shortcut: NormalizedShortcut,
+    pub score: i64,
+}
+
+// 設定の再読み込みで変化したショートカット（アプリ名とアクション名で対応付け）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShortcutsDiff {
+    pub added: Vec<NormalizedShortcut>,
+    pub removed: Vec<NormalizedShortcut>,
+    /// キーやタグが変わったもの（変更後の内容）
+    pub changed: Vec<NormalizedShortcut>,
+}
+
+impl ShortcutsDiff {
+    /// 変化がないかどうか
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+// ショートカットの使用回数（usage.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEntry {
+    pub app: String,
+    pub action: String,
+    pub count: u64,
+}
+
+// お気に入りに登録したショートカット（favorites.json）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FavoriteEntry {
+    pub app: String,
+    pub action: String,
+}
+
+// アプリごとの概要（アプリ一覧表示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct AppSummary {
+    pub name: String,
+    pub icon: String,
+    pub shortcut_count: usize,
 }
 
 // 正規化されたアプリ情報（フロントエンドに渡す用）
@@ -128,13 +347,41 @@ pub struct NormalizedShortcut {
 pub struct NormalizedApp {
     pub name: String,
     pub icon: String,
+    /// 自動取得したアイコン画像（PNGの data URL）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// ドキュメントURL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 // アクティブウィンドウ情報
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub struct ActiveWindowInfo {
     pub process: Option<String>,
     pub window: Option<String>,
+    /// 実行ファイルのフルパス（アイコンの自動取得用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
+    /// 所有者の最上位ウィンドウ（ダイアログなどの場合、Windowsのみ）
+    /// 最前面のウィンドウにマッチするアプリがない場合にマッチングに使う
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<Box<Self>>,
+}
+
+// window-shownイベントのペイロード（マッチしたアプリも含め、フロントエンドがすぐに描画できるようにする）
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowShownPayload {
+    pub active_window: Option<ActiveWindowInfo>,
+    pub matched_apps: Vec<NormalizedApp>,
+    pub escape_to_hide: bool,
+}
+
+// second-instanceイベントのペイロード（2つ目の起動時に渡された引数、実行ファイル名は除く）
+#[derive(Debug, Clone, Serialize)]
+pub struct SecondInstancePayload {
+    pub args: Vec<String>,
+    pub cwd: String,
 }
 
 // テーマ設定
@@ -147,6 +394,182 @@ pub enum ThemeSetting {
     Dark,
 }
 
+// ショートカットの主表示（アクションとキーのどちらを先に表示するか）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrimaryLabel {
+    #[default]
+    Action,
+    Key,
+}
+
+// キーの表示形式（verbose: "Command + Shift + K", symbols: macOSで "⌘ + ⇧ + K"）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyDisplayStyle {
+    #[default]
+    Verbose,
+    Symbols,
+}
+
+// ショートカットの表示方法（overlay: オーバーレイウィンドウ、notification: OSの通知）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayMode {
+    #[default]
+    Overlay,
+    Notification,
+}
+
+// ショートカット一覧の並び順（config: 設定順、app / action / key: 名前順）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortcutSort {
+    #[default]
+    Config,
+    App,
+    Action,
+    Key,
+}
+
+impl ShortcutSort {
+    /// 並び順の名前（"config" / "app" / "action" / "key"）から取得
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "config" => Some(Self::Config),
+            "app" => Some(Self::App),
+            "action" => Some(Self::Action),
+            "key" => Some(Self::Key),
+            _ => None,
+        }
+    }
+}
+
+// 表示言語の設定
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageSetting {
+    #[default]
+    System,
+    Ja,
+    En,
+}
+
+// 実際に使用する表示言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Ja,
+    En,
+}
+
+impl LanguageSetting {
+    /// 設定から表示言語を決定（systemの場合はOSの言語設定に従う）
+    pub fn resolve(self) -> Language {
+        match self {
+            Self::Ja => Language::Ja,
+            Self::En => Language::En,
+            Self::System => {
+                let is_japanese = sys_locale::get_locale()
+                    .is_some_and(|locale| locale.to_lowercase().starts_with("ja"));
+                if is_japanese {
+                    Language::Ja
+                } else {
+                    Language::En
+                }
+            }
+        }
+    }
+}
+
+// トレイメニューの表示文字列
+struct TrayLabels {
+    detected_apps: &'static str,
+    no_detected_apps: &'static str,
+    show: &'static str,
+    pin: &'static str,
+    keybindings: &'static str,
+    config: &'static str,
+    update: &'static str,
+    about: &'static str,
+    quit: &'static str,
+}
+
+impl Language {
+    /// トレイメニューの表示文字列を取得
+    const fn tray_labels(self) -> TrayLabels {
+        match self {
+            Self::Ja => TrayLabels {
+                detected_apps: "検出中のアプリ",
+                no_detected_apps: "（なし）",
+                show: "ウィンドウを表示",
+                pin: "ウィンドウを固定表示",
+                keybindings: "キーバインド設定",
+                config: "設定ファイルを開く",
+                update: "アップデートを確認",
+                about: "About",
+                quit: "終了",
+            },
+            Self::En => TrayLabels {
+                detected_apps: "Detected Apps",
+                no_detected_apps: "(None)",
+                show: "Show Window",
+                pin: "Keep Window Open",
+                keybindings: "Keybindings",
+                config: "Open Config File",
+                update: "Check for Updates",
+                about: "About",
+                quit: "Quit",
+            },
+        }
+    }
+
+    /// トレイのツールチップ文字列を作成
+    fn tray_tooltip(self, process: &str, window: &str) -> String {
+        match self {
+            Self::Ja => format!("Finkey — アクティブ: {process} / {window}"),
+            Self::En => format!("Finkey — active: {process} / {window}"),
+        }
+    }
+
+    /// 設定ファイルを読み込めなかった場合のメッセージ
+    fn read_error_message(self, path: &Path, error: &str) -> String {
+        match self {
+            Self::Ja => format!("{} の読み込みに失敗しました: {error}", path.display()),
+            Self::En => format!("Failed to read {}: {error}", path.display()),
+        }
+    }
+
+    /// 設定ファイルをパースできなかった場合のメッセージ
+    fn parse_error_message(self, path: &Path, error: &str) -> String {
+        match self {
+            Self::Ja => format!("{} のパースに失敗しました: {error}", path.display()),
+            Self::En => format!("Failed to parse {}: {error}", path.display()),
+        }
+    }
+
+    /// パースできなかったファイルのバックアップ結果をエラーに添える
+    fn broken_file_message(self, error: &str, backup: Result<&Path, &std::io::Error>) -> String {
+        match (self, backup) {
+            (Self::Ja, Ok(backup)) => format!("{error}（バックアップ: {}）", backup.display()),
+            (Self::Ja, Err(e)) => format!("{error}（バックアップ作成エラー: {e}）"),
+            (Self::En, Ok(backup)) => format!("{error} (backup: {})", backup.display()),
+            (Self::En, Err(e)) => format!("{error} (failed to create backup: {e})"),
+        }
+    }
+
+    /// 読み込めないキーバインド設定への保存を拒否する場合のメッセージ
+    fn keybindings_not_writable_message(self, error: &str) -> String {
+        match self {
+            Self::Ja => format!(
+                "キーバインド設定ファイルを読み込めないため保存できません。ファイルを修正してください: {error}"
+            ),
+            Self::En => format!(
+                "The keybindings file cannot be read, so changes were not saved. Please fix the file: {error}"
+            ),
+        }
+    }
+}
+
 // オーバーレイの位置
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OverlayPosition {
@@ -154,6 +577,70 @@ pub struct OverlayPosition {
     pub y: Option<i32>,
 }
 
+// 設定ファイルの場所（設定画面での表示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigPaths {
+    pub config_dir: String,
+    pub keybindings: String,
+    pub settings: String,
+}
+
+// keybindings.json の検証で見つかった警告（設定画面で該当箇所を強調表示する用）
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigWarning {
+    /// 同じ名前のアプリが複数定義されている
+    DuplicateApp { app: String },
+    /// 同じアプリ内で同じアクションが複数定義されている
+    DuplicateAction { app: String, action: String },
+    /// 同じアプリ内で同じキーに複数のアクションが割り当てられている
+    KeyConflict {
+        app: String,
+        key: String,
+        actions: Vec<String>,
+    },
+    /// `os` を指定したアプリのOS別のキーに、そのOSのキーがない
+    MissingOsKey {
+        app: String,
+        action: String,
+        os: OsType,
+    },
+}
+
+// keybindings.json のサイズと項目数（同期サービスの容量監視用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSizeInfo {
+    pub bytes: u64,
+    pub app_count: usize,
+    pub keybinding_count: usize,
+}
+
+// クイックリファレンスカードの1行（幅はオーバーレイと同じ計算による論理ピクセル）
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickCardRow {
+    pub action: String,
+    pub key: String,
+    pub width: f64,
+}
+
+// 1つのアプリのクイックリファレンスカード（幅は各行の最大値）
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickCard {
+    pub app: String,
+    pub icon: String,
+    pub rows: Vec<QuickCardRow>,
+    pub width: f64,
+}
+
+// メインウィンドウの位置とサイズ（物理ピクセル）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WindowBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
 // デフォルト設定の構造体（defaults/settings.json用、すべてのフィールドが必須）
 #[derive(Debug, Clone, Deserialize)]
 struct DefaultSettings {
@@ -167,18 +654,79 @@ static DEFAULT_SETTINGS_CACHE: OnceLock<DefaultSettings> = OnceLock::new();
 
 // アプリ設定（settings.json）
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // settings.json の各項目をそのまま対応させるため
 pub struct AppSettings {
     #[serde(default = "default_theme")]
     pub theme: ThemeSetting,
     /// アプリ起動のホットキー（例: "Ctrl+Shift+K"）
     #[serde(default = "default_hotkey")]
     pub hotkey: String,
+    /// 追加の起動ホットキー（hotkey と合わせてすべて登録）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hotkeys: Vec<String>,
     /// オーバーレイ表示時間（秒）
     #[serde(default = "default_overlay_duration")]
     pub overlay_duration: u32,
     /// オーバーレイの位置（ドラッグで移動した場合に保存）
     #[serde(default)]
     pub overlay_position: OverlayPosition,
+    /// 設定ファイルのパースエラー時にデフォルトへ戻さずエラーを通知する
+    #[serde(default)]
+    pub strict_config: bool,
+    /// ログイン時に自動起動する
+    #[serde(default)]
+    pub autostart: bool,
+    /// メインウィンドウの位置とサイズ（移動・リサイズ時に保存）
+    #[serde(default)]
+    pub window_bounds: Option<WindowBounds>,
+    /// 保存した位置を使わず、常に中央に表示する
+    #[serde(default)]
+    pub always_center: bool,
+    /// 表示言語（system / ja / en）
+    #[serde(default)]
+    pub language: LanguageSetting,
+    /// 主表示（action / key）。エクスポートの列順にも使用
+    #[serde(default)]
+    pub primary_label: PrimaryLabel,
+    /// すべてのショートカットにタグを必須とする（違反は `get_policy_violations` で確認）
+    #[serde(default)]
+    pub require_tags: bool,
+    /// オーバーレイの最小幅（論理ピクセル、省略時は既定値）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_min_width: Option<f64>,
+    /// オーバーレイの最大幅（論理ピクセル、省略時は既定値）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_max_width: Option<f64>,
+    /// キーの表示形式（verbose / symbols）。symbols の場合、macOS では修飾キーを記号で表示
+    #[serde(default)]
+    pub key_display_style: KeyDisplayStyle,
+    /// 最前面のウィンドウにマッチするアプリがない場合、所有者ウィンドウ（ダイアログの親アプリなど）でマッチする（Windowsのみ）
+    #[serde(default)]
+    pub match_owner_window: bool,
+    /// ショートカットの表示方法（overlay / notification）
+    #[serde(default)]
+    pub overlay_mode: OverlayMode,
+    /// オーバーレイ表示時に鳴らす効果音（ファイルパスまたはOSのサウンド名、空なら無音）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_sound: Option<String>,
+    /// 検索ウィンドウで Esc キーを押したらウィンドウを閉じる
+    #[serde(default = "default_escape_to_hide")]
+    pub escape_to_hide: bool,
+    /// アクティブウィンドウを監視する（無効にするとアプリの検出が止まる）
+    #[serde(default = "default_active_monitor")]
+    pub active_monitor: bool,
+    /// ショートカット一覧の初期の並び順
+    #[serde(default)]
+    pub default_shortcut_sort: ShortcutSort,
+    /// keybindings.json をデフォルト設定に重ねて使用する（同じ名前のアプリは置き換え）
+    #[serde(default)]
+    pub merge_defaults: bool,
+    /// オーバーレイを表示しない時間帯の開始（"HH:MM"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours_start: Option<String>,
+    /// オーバーレイを表示しない時間帯の終了（"HH:MM"、開始より前なら日をまたぐ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours_end: Option<String>,
 }
 
 // デフォルト設定のJSONを読み込み（プラットフォーム別）
@@ -186,6 +734,8 @@ pub struct AppSettings {
 const DEFAULT_SETTINGS_JSON: &str = include_str!("../defaults/windows/settings.json");
 #[cfg(target_os = "macos")]
 const DEFAULT_SETTINGS_JSON: &str = include_str!("../defaults/macos/settings.json");
+#[cfg(target_os = "linux")]
+const DEFAULT_SETTINGS_JSON: &str = include_str!("../defaults/linux/settings.json");
 
 /// defaults/settings.json から設定を取得（初回のみパースしてキャッシュ）
 fn get_defaults() -> &'static DefaultSettings {
@@ -207,6 +757,14 @@ fn default_overlay_duration() -> u32 {
     get_defaults().overlay_duration
 }
 
+const fn default_escape_to_hide() -> bool {
+    true
+}
+
+const fn default_active_monitor() -> bool {
+    true
+}
+
 /// ショートカットキー文字列を正規化（Tauri API用）
 /// スペースあり/なし両方の入力形式を受け付け、スペースなし形式に変換
 fn normalize_hotkey_for_tauri(key: &str) -> String {
@@ -214,6 +772,27 @@ fn normalize_hotkey_for_tauri(key: &str) -> String {
     key.replace(" + ", "+")
 }
 
+/// 空白区切りの順次入力（"g d"、"Ctrl+K Ctrl+S"）を "->" 区切りに変換
+/// "Page Down" のように空白を含むキー名と区別するため、空白で区切った各部分が
+/// 1文字のキーか "+" を含む組み合わせの場合のみ順次入力とみなす
+fn normalize_key_sequence(key: &str) -> String {
+    let key = key.trim();
+    if key.contains("->") {
+        return key.to_string();
+    }
+    let compact = normalize_hotkey_for_tauri(key);
+    let steps: Vec<&str> = compact.split_whitespace().collect();
+    let is_sequence = steps.len() > 1
+        && steps
+            .iter()
+            .all(|step| step.chars().count() == 1 || step.contains('+'));
+    if is_sequence {
+        steps.join(" -> ")
+    } else {
+        key.to_string()
+    }
+}
+
 /// ショートカットキー文字列を正規化（表示用）
 /// スペースあり/なし両方の入力形式を受け付け、スペースあり形式に変換
 fn normalize_key_for_display(key: &str) -> String {
@@ -249,9 +828,97 @@ impl Default for AppSettings {
         Self {
             theme: defaults.theme.clone(),
             hotkey: defaults.hotkey.clone(),
+            hotkeys: Vec::new(),
             overlay_duration: defaults.overlay_duration,
             overlay_position: OverlayPosition::default(),
+            strict_config: false,
+            autostart: false,
+            window_bounds: None,
+            always_center: false,
+            language: LanguageSetting::default(),
+            primary_label: PrimaryLabel::default(),
+            require_tags: false,
+            overlay_min_width: None,
+            overlay_max_width: None,
+            key_display_style: KeyDisplayStyle::default(),
+            match_owner_window: false,
+            overlay_mode: OverlayMode::default(),
+            overlay_sound: None,
+            escape_to_hide: default_escape_to_hide(),
+            active_monitor: default_active_monitor(),
+            default_shortcut_sort: ShortcutSort::default(),
+            merge_defaults: false,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+        }
+    }
+}
+
+impl AppSettings {
+    /// 起動ホットキーの一覧（hotkey を先頭に hotkeys を統合、同じキーの重複は除外）
+    pub fn all_hotkeys(&self) -> Vec<String> {
+        let mut seen = Vec::new();
+        std::iter::once(&self.hotkey)
+            .chain(&self.hotkeys)
+            .filter(|hotkey| {
+                // パースできないものはエラー報告のため残す
+                parse_hotkey(hotkey).is_none_or(|shortcut| {
+                    let is_new = !seen.contains(&shortcut);
+                    seen.push(shortcut);
+                    is_new
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// オーバーレイの幅の範囲（最小, 最大）
+    /// 設定値が不正な場合（手動編集など）は既定値を使用
+    pub fn overlay_width_range(&self) -> (f64, f64) {
+        let min = self.overlay_min_width.unwrap_or(OVERLAY_MIN_WIDTH);
+        let max = self.overlay_max_width.unwrap_or(OVERLAY_MAX_WIDTH);
+        if validate_overlay_width_range(min, max).is_ok() {
+            (min, max)
+        } else {
+            (OVERLAY_MIN_WIDTH, OVERLAY_MAX_WIDTH)
+        }
+    }
+
+    /// オーバーレイを表示しない時間帯（開始, 終了）を0時からの分数で取得
+    /// 未設定または形式が不正な場合は None
+    pub fn quiet_hours(&self) -> Option<(u32, u32)> {
+        let start = parse_time_of_day(self.quiet_hours_start.as_deref()?)?;
+        let end = parse_time_of_day(self.quiet_hours_end.as_deref()?)?;
+        Some((start, end))
+    }
+
+    /// 同じキーに解釈される起動ホットキーの組を「項目名（設定値） / ...」形式で列挙
+    pub fn hotkey_collisions(&self) -> Vec<String> {
+        let entries = std::iter::once(("hotkey".to_string(), &self.hotkey)).chain(
+            self.hotkeys
+                .iter()
+                .enumerate()
+                .map(|(i, hotkey)| (format!("hotkeys[{i}]"), hotkey)),
+        );
+        let mut groups: Vec<(Shortcut, Vec<String>)> = Vec::new();
+        for (name, hotkey) in entries {
+            let Some(shortcut) = parse_hotkey(hotkey) else {
+                continue;
+            };
+            let label = format!("{name}（{hotkey}）");
+            match groups
+                .iter_mut()
+                .find(|(existing, _)| *existing == shortcut)
+            {
+                Some((_, labels)) => labels.push(label),
+                None => groups.push((shortcut, vec![label])),
+            }
         }
+        groups
+            .into_iter()
+            .filter(|(_, labels)| labels.len() > 1)
+            .map(|(_, labels)| labels.join(" / "))
+            .collect()
     }
 }
 
@@ -260,26 +927,98 @@ impl Default for AppSettings {
 const DEFAULT_KEYBINDINGS_JSON: &str = include_str!("../defaults/windows/keybindings.json");
 #[cfg(target_os = "macos")]
 const DEFAULT_KEYBINDINGS_JSON: &str = include_str!("../defaults/macos/keybindings.json");
+#[cfg(target_os = "linux")]
+const DEFAULT_KEYBINDINGS_JSON: &str = include_str!("../defaults/linux/keybindings.json");
 
 fn get_default_keybindings() -> Vec<AppConfig> {
     serde_json::from_str::<Vec<AppConfig>>(DEFAULT_KEYBINDINGS_JSON)
         .expect("defaults/keybindings.json のパースに失敗しました。ファイルが正しいJSON形式か確認してください。")
 }
 
-// 設定ディレクトリのパスを取得
+thread_local! {
+    // 設定ディレクトリの差し替え先（テストで実際の設定に触れないようにするため）
+    static CONFIG_DIR_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+// 設定ディレクトリのパスを取得（差し替え先が設定されていればそちらを使う）
 fn get_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = CONFIG_DIR_OVERRIDE.with_borrow(Clone::clone) {
+        return Some(dir);
+    }
     let config_dir = dirs::config_dir()?;
     Some(config_dir.join("finkey"))
 }
 
-// キーバインド設定ファイルのパスを取得
+// 設定ファイルの場所を取得するコマンド（ファイルが未作成でもパスを返す）
+#[tauri::command]
+fn get_config_paths() -> Result<ConfigPaths, String> {
+    let not_found = "設定ディレクトリが見つかりません";
+    let config_dir = get_config_dir().ok_or(not_found)?;
+    let keybindings = get_keybindings_config_path().ok_or(not_found)?;
+    let settings = get_settings_path().ok_or(not_found)?;
+    Ok(ConfigPaths {
+        config_dir: config_dir.display().to_string(),
+        keybindings: keybindings.display().to_string(),
+        settings: settings.display().to_string(),
+    })
+}
+
+// keybindings.json のファイルサイズとアプリ・キーバインドの数を取得するコマンド
+// ファイルが未作成の場合のサイズは0（項目数はデフォルト設定のもの）
+#[tauri::command]
+fn get_config_size_info() -> ConfigSizeInfo {
+    let bytes = get_keybindings_config_path()
+        .and_then(|path| fs::metadata(path).ok())
+        .map_or(0, |metadata| metadata.len());
+    let config = load_keybindings_config();
+    ConfigSizeInfo {
+        bytes,
+        app_count: config.len(),
+        keybinding_count: config.iter().map(|app| app.keybindings.len()).sum(),
+    }
+}
+
+/// 設定ファイルのパスを決定（JSONを優先し、JSONがなくYAMLがある場合のみYAML）
+fn resolve_config_file(dir: &Path, stem: &str) -> PathBuf {
+    let json_path = dir.join(format!("{stem}.json"));
+    let yaml_path = dir.join(format!("{stem}.yaml"));
+    if !json_path.exists() && yaml_path.exists() {
+        yaml_path
+    } else {
+        json_path
+    }
+}
+
+/// YAML形式の設定ファイルかどうか
+fn is_yaml_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "yaml")
+}
+
+/// ファイル名の末尾に文字列を付けたパス（例: keybindings.json → keybindings.json.bak）
+fn with_file_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// キーバインド設定ファイルのパスを取得（keybindings.json または keybindings.yaml）
 fn get_keybindings_config_path() -> Option<PathBuf> {
-    Some(get_config_dir()?.join("keybindings.json"))
+    Some(resolve_config_file(&get_config_dir()?, "keybindings"))
 }
 
-// アプリ設定ファイルのパスを取得
+// アプリ設定ファイルのパスを取得（settings.json または settings.yaml）
 fn get_settings_path() -> Option<PathBuf> {
-    Some(get_config_dir()?.join("settings.json"))
+    Some(resolve_config_file(&get_config_dir()?, "settings"))
+}
+
+// 使用回数ファイルのパスを取得
+fn get_usage_path() -> Option<PathBuf> {
+    Some(get_config_dir()?.join("usage.json"))
+}
+
+// お気に入りファイルのパスを取得
+fn get_favorites_path() -> Option<PathBuf> {
+    Some(get_config_dir()?.join("favorites.json"))
 }
 
 // アプリ設定を読み込む（キャッシュ付き）
@@ -291,25 +1030,35 @@ fn load_settings() -> AppSettings {
     let current_modified = get_file_modified_time(&path);
 
     // キャッシュをチェック
-    if let Ok(cache_guard) = SETTINGS_CACHE.lock() {
-        if let Some(ref cache) = *cache_guard {
-            // タイムスタンプが同じならキャッシュを返す
-            if cache.last_modified == current_modified && current_modified.is_some() {
-                return cache.data.clone();
-            }
+    let previous_settings = match *read_cache(&SETTINGS_CACHE) {
+        // タイムスタンプが同じならキャッシュを返す
+        Some(ref cache)
+            if cache.last_modified == current_modified && current_modified.is_some() =>
+        {
+            return cache.data.clone();
         }
-    }
+        Some(ref cache) => Some(cache.data.clone()),
+        None => None,
+    };
 
     // ファイルを読み込む
     let settings = if path.exists() {
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|content| serde_json::from_str::<AppSettings>(&content).ok())
-            .unwrap_or_else(|| {
+        match read_config_file::<AppSettings>(&path) {
+            Ok(settings) => {
+                set_config_error(&SETTINGS_CONFIG_ERROR, None);
+                settings
+            }
+            Err(e) if is_strict_config_requested(&path, previous_settings.as_ref()) => {
+                // 厳格モード: ファイルを上書きせず、直前の設定のままエラーを通知
+                set_config_error(&SETTINGS_CONFIG_ERROR, Some(e));
+                previous_settings.unwrap_or_default()
+            }
+            Err(_) => {
                 let default = AppSettings::default();
                 let _ = save_settings(&default);
                 default
-            })
+            }
+        }
     } else {
         let default = AppSettings::default();
         let _ = save_settings(&default);
@@ -317,12 +1066,10 @@ fn load_settings() -> AppSettings {
     };
 
     // キャッシュを更新
-    if let Ok(mut cache_guard) = SETTINGS_CACHE.lock() {
-        *cache_guard = Some(SettingsCache {
-            data: settings.clone(),
-            last_modified: get_file_modified_time(&path),
-        });
-    }
+    *write_cache(&SETTINGS_CACHE) = Some(SettingsCache {
+        data: settings.clone(),
+        last_modified: get_file_modified_time(&path),
+    });
 
     settings
 }
@@ -336,17 +1083,14 @@ fn save_settings(settings: &AppSettings) -> Result<(), String> {
         fs::create_dir_all(parent).map_err(|e| format!("ディレクトリ作成エラー: {e}"))?;
     }
 
-    let json =
-        serde_json::to_string_pretty(settings).map_err(|e| format!("JSON変換エラー: {e}"))?;
-    fs::write(&path, json).map_err(|e| format!("ファイル書き込みエラー: {e}"))?;
+    let content = serialize_config(&path, settings)?;
+    fs::write(&path, content).map_err(|e| format!("ファイル書き込みエラー: {e}"))?;
 
     // キャッシュを更新
-    if let Ok(mut cache_guard) = SETTINGS_CACHE.lock() {
-        *cache_guard = Some(SettingsCache {
-            data: settings.clone(),
-            last_modified: get_file_modified_time(&path),
-        });
-    }
+    *write_cache(&SETTINGS_CACHE) = Some(SettingsCache {
+        data: settings.clone(),
+        last_modified: get_file_modified_time(&path),
+    });
 
     Ok(())
 }
@@ -356,53 +1100,432 @@ fn get_file_modified_time(path: &PathBuf) -> Option<SystemTime> {
     fs::metadata(path).ok()?.modified().ok()
 }
 
-// キーバインド設定を読み込む（キャッシュ付き）
-fn load_keybindings_config() -> Vec<AppConfig> {
-    let Some(path) = get_keybindings_config_path() else {
-        return get_default_keybindings();
-    };
-
-    let current_modified = get_file_modified_time(&path);
+/// 設定ファイルを読み込んでパース（エラー時は行・列を含むメッセージを返す）
+fn read_config_file<T: DeserializeOwned>(path: &PathBuf) -> Result<T, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("{} の読み込みに失敗しました: {e}", path.display()))?;
+    parse_config_str::<T>(path, &content)
+        .map_err(|e| format!("{} のパースに失敗しました: {e}", path.display()))
+}
 
-    // キャッシュをチェック
-    if let Ok(cache_guard) = KEYBINDINGS_CACHE.lock() {
-        if let Some(ref cache) = *cache_guard {
-            // タイムスタンプが同じならキャッシュを返す
-            if cache.last_modified == current_modified && current_modified.is_some() {
-                return cache.data.clone();
-            }
-        }
+/// 設定ファイルの内容をパース（拡張子に応じてJSONまたはYAML）
+fn parse_config_str<T: DeserializeOwned>(path: &Path, content: &str) -> Result<T, String> {
+    if is_yaml_path(path) {
+        serde_yaml_ng::from_str::<T>(content).map_err(|e| e.to_string())
+    } else {
+        parse_json_relaxed::<T>(content).map_err(|e| e.to_string())
     }
+}
 
-    // ファイルを読み込む
-    let config = if path.exists() {
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|content| serde_json::from_str::<Vec<AppConfig>>(&content).ok())
-            .unwrap_or_else(|| {
-                let default = get_default_keybindings();
-                let _ = save_keybindings_config(&default);
-                default
-            })
+/// JSONをパース（厳密なJSONとして読めない場合はJSON5としてコメントや末尾カンマを許容）
+/// どちらでも読めない場合は行・列が正確な `serde_json` のエラーを返す
+fn parse_json_relaxed<T: DeserializeOwned>(content: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str::<T>(content).or_else(|e| json5::from_str::<T>(content).map_err(|_| e))
+}
+
+/// 設定を拡張子に応じた形式（JSONまたはYAML）の文字列に変換
+fn serialize_config<T: Serialize>(path: &Path, value: &T) -> Result<String, String> {
+    if is_yaml_path(path) {
+        serde_yaml_ng::to_string(value).map_err(|e| format!("YAML変換エラー: {e}"))
     } else {
-        let default = get_default_keybindings();
-        let _ = save_keybindings_config(&default);
-        default
-    };
+        serde_json::to_string_pretty(value).map_err(|e| format!("JSON変換エラー: {e}"))
+    }
+}
 
-    // キャッシュを更新
-    if let Ok(mut cache_guard) = KEYBINDINGS_CACHE.lock() {
-        *cache_guard = Some(KeybindingsCache {
-            data: config.clone(),
-            last_modified: get_file_modified_time(&path),
-        });
+/// 厳格モード（`strict_config`）が有効かどうか
+/// settings.json 自体が壊れている場合は、JSONとして読める範囲の値か直前の設定で判定
+fn is_strict_config_requested(settings_path: &PathBuf, previous: Option<&AppSettings>) -> bool {
+    fs::read_to_string(settings_path)
+        .ok()
+        .and_then(|content| parse_config_str::<serde_json::Value>(settings_path, &content).ok())
+        .and_then(|value| value.get("strict_config")?.as_bool())
+        .or_else(|| previous.map(|settings| settings.strict_config))
+        .unwrap_or(false)
+}
+
+/// 設定ファイルのエラーを記録（Noneでクリア）
+fn set_config_error(slot: &Mutex<Option<String>>, error: Option<String>) {
+    if let Ok(mut guard) = slot.lock() {
+        *guard = error;
+    }
+}
+
+// 追加のキーバインド設定ファイルを置くディレクトリ名（設定ディレクトリ内）
+const KEYBINDINGS_DIR_NAME: &str = "keybindings.d";
+
+// keybindings.json の現在の形式バージョン
+// 形式を変える場合はこの値を上げ、migrate_config_step に移行処理を追加する
+const KEYBINDINGS_CONFIG_VERSION: u64 = 1;
+
+/// keybindings.json の形式バージョンを取得（バージョンのない配列形式は 0）
+fn config_version(value: &serde_json::Value) -> Result<u64, String> {
+    match value {
+        serde_json::Value::Array(_) => Ok(0),
+        serde_json::Value::Object(object) => object
+            .get("version")
+            .and_then(serde_json::Value::as_u64)
+            .ok_or_else(|| "version が数値で指定されていません".to_string()),
+        _ => Err("設定は配列または version を含むオブジェクトである必要があります".to_string()),
+    }
+}
+
+/// 1つ前のバージョンの形式から指定したバージョンの形式へ変換
+fn migrate_config_step(to_version: u64, value: serde_json::Value) -> serde_json::Value {
+    match to_version {
+        // v0 → v1: アプリ設定の配列を version 付きのオブジェクトで包む
+        1 => serde_json::json!({ "version": 1, "apps": value }),
+        _ => value,
+    }
+}
+
+/// 古い形式の keybindings.json を現在の形式に変換してパース
+fn migrate_config(mut value: serde_json::Value) -> Result<Vec<AppConfig>, String> {
+    let version = config_version(&value)?;
+    if version > KEYBINDINGS_CONFIG_VERSION {
+        return Err(format!(
+            "新しいバージョン（{version}）の設定ファイルのため読み込めません。アプリを更新してください"
+        ));
+    }
+    for to_version in version + 1..=KEYBINDINGS_CONFIG_VERSION {
+        value = migrate_config_step(to_version, value);
+    }
+    let apps = value
+        .get_mut("apps")
+        .map(serde_json::Value::take)
+        .ok_or("apps が見つかりません")?;
+    serde_json::from_value::<Vec<AppConfig>>(apps).map_err(|e| e.to_string())
+}
+
+// 現在の形式の keybindings.json（型の誤りを行・列付きで報告するための直接パース用）
+#[derive(Deserialize)]
+struct KeybindingsFile {
+    apps: Vec<AppConfig>,
+}
+
+/// キーバインド設定の内容をパース（旧形式も受け付け、型の誤りも可能な限り行・列付きで報告）
+fn parse_keybindings_str(path: &Path, content: &str) -> Result<Vec<AppConfig>, String> {
+    let value = parse_config_str::<serde_json::Value>(path, content)?;
+    match config_version(&value)? {
+        0 => parse_config_str::<Vec<AppConfig>>(path, content),
+        KEYBINDINGS_CONFIG_VERSION => {
+            parse_config_str::<KeybindingsFile>(path, content).map(|file| file.apps)
+        }
+        _ => migrate_config(value),
+    }
+}
+
+/// keybindings.json を読み込み、古い形式であれば現在の形式に変換
+/// 戻り値の数値は変換前のファイルのバージョン
+fn read_keybindings_file(path: &PathBuf) -> Result<(Vec<AppConfig>, u64), String> {
+    // 読み込みエラーは通知されるため、表示言語に合わせる
+    let language = load_settings().language.resolve();
+    let content =
+        fs::read_to_string(path).map_err(|e| language.read_error_message(path, &e.to_string()))?;
+    let parse_error = |e: String| language.parse_error_message(path, &e);
+    let value = parse_config_str::<serde_json::Value>(path, &content).map_err(parse_error)?;
+    let version = config_version(&value).map_err(parse_error)?;
+    let config = migrate_config(value).map_err(parse_error)?;
+    Ok((config, version))
+}
+
+/// 古い形式の keybindings.json を現在の形式で書き直す
+/// 元の内容（コメントや書式を含む）は `keybindings.json.v<バージョン>.bak` に残し、既にあれば上書きしない
+fn migrate_keybindings_file(
+    path: &Path,
+    version: u64,
+    config: &Vec<AppConfig>,
+) -> Result<(), String> {
+    let backup_path = with_file_suffix(path, &format!(".v{version}.bak"));
+    if !backup_path.exists() {
+        fs::copy(path, &backup_path).map_err(|e| format!("バックアップ作成エラー: {e}"))?;
+    }
+    write_keybindings_config(config)
+}
+
+// キーバインド設定を読み込む（keybindings.d/ の設定を追加し、merge_defaults 有効時はデフォルト設定と統合済み）
+// 呼び出しごとに全体を複製しないよう、キャッシュと共有する Arc で返す
+fn load_keybindings_config() -> Arc<Vec<AppConfig>> {
+    build_effective_config(load_user_keybindings_config())
+}
+
+/// keybindings.json の内容に keybindings.d/ の設定を続け、
+/// `merge_defaults` 設定が有効ならデフォルト設定に重ねる
+/// どちらも不要な場合は複製せずにそのまま返し、統合した結果は元の内容が変わるまで使い回す
+fn build_effective_config(config: Arc<Vec<AppConfig>>) -> Arc<Vec<AppConfig>> {
+    let extra = load_keybindings_dir();
+    let merge_defaults = load_settings().merge_defaults;
+    if extra.is_empty() && !merge_defaults {
+        return config;
+    }
+
+    // 元になった設定（キャッシュの Arc）と merge_defaults が同じならキャッシュを返す
+    // （keybindings.d/ がない場合は呼び出しごとに空の Arc が作られるため、空同士も同じとみなす）
+    if let Some(ref cache) = *read_cache(&EFFECTIVE_KEYBINDINGS_CACHE) {
+        let same_extra =
+            Arc::ptr_eq(&cache.extra, &extra) || (cache.extra.is_empty() && extra.is_empty());
+        if Arc::ptr_eq(&cache.user, &config) && same_extra && cache.merge_defaults == merge_defaults
+        {
+            return Arc::clone(&cache.data);
+        }
+    }
+
+    let mut merged = config.to_vec();
+    merged.extend(extra.iter().cloned());
+    if merge_defaults {
+        merged = merge_app_configs(get_default_keybindings(), merged);
+    }
+    let merged = Arc::new(merged);
+    *write_cache(&EFFECTIVE_KEYBINDINGS_CACHE) = Some(EffectiveKeybindingsCache {
+        user: config,
+        extra,
+        merge_defaults,
+        data: Arc::clone(&merged),
+    });
+    merged
+}
+
+/// keybindings.d/ 内の *.json をファイル名順に読み込んで連結（キャッシュ付き）
+/// パースできないファイルは警告を出して読み飛ばす
+fn load_keybindings_dir() -> Arc<Vec<AppConfig>> {
+    let Some(dir) = get_config_dir().map(|dir| dir.join(KEYBINDINGS_DIR_NAME)) else {
+        return Arc::default();
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Arc::default();
+    };
+    let mut files: Vec<(PathBuf, Option<SystemTime>)> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| {
+            let modified = get_file_modified_time(&path);
+            (path, modified)
+        })
+        .collect();
+    files.sort();
+
+    // ファイルの一覧と更新時刻が同じならキャッシュを返す
+    if let Some((ref cached_files, ref data)) = *read_cache(&KEYBINDINGS_DIR_CACHE) {
+        if *cached_files == files {
+            return Arc::clone(data);
+        }
+    }
+
+    let mut config = Vec::new();
+    for (path, _) in &files {
+        match read_keybindings_file(path) {
+            Ok((apps, _)) => config.extend(apps),
+            Err(e) => eprintln!("Warning: {e}（このファイルは読み飛ばします）"),
+        }
+    }
+
+    let config = Arc::new(config);
+    *write_cache(&KEYBINDINGS_DIR_CACHE) = Some((files, Arc::clone(&config)));
+    config
+}
+
+/// アプリ設定の一覧に別の一覧を重ねる（デフォルト設定との統合やインポートに使用）
+/// 同じ名前のアプリは後者で置き換え（位置は前者のまま）、新しいアプリは末尾に追加
+fn merge_app_configs(base: Vec<AppConfig>, overlay: Vec<AppConfig>) -> Vec<AppConfig> {
+    let mut overlay: Vec<Option<AppConfig>> = overlay.into_iter().map(Some).collect();
+    let mut merged: Vec<AppConfig> = base
+        .into_iter()
+        .map(|app| {
+            let name = app.get_name();
+            overlay
+                .iter_mut()
+                .find(|other| other.as_ref().is_some_and(|other| other.get_name() == name))
+                .and_then(Option::take)
+                .unwrap_or(app)
+        })
+        .collect();
+    merged.extend(overlay.into_iter().flatten());
+    merged
+}
+
+// keybindings.json の内容を読み込む（キャッシュ付き、デフォルト設定とは統合しない）
+fn load_user_keybindings_config() -> Arc<Vec<AppConfig>> {
+    let Some(path) = get_keybindings_config_path() else {
+        return Arc::new(get_default_keybindings());
+    };
+
+    let current_modified = get_file_modified_time(&path);
+
+    // キャッシュをチェック
+    if let Some(ref cache) = *read_cache(&KEYBINDINGS_CACHE) {
+        // タイムスタンプが同じならキャッシュを返す
+        if cache.last_modified == current_modified && current_modified.is_some() {
+            return Arc::clone(&cache.data);
+        }
     }
 
+    // ファイルを読み込む
+    let config = if path.exists() {
+        match read_keybindings_file(&path) {
+            Ok((config, version)) => {
+                set_config_error(&KEYBINDINGS_CONFIG_ERROR, None);
+                // 古い形式から変換した場合は現在の形式で書き直す
+                if version < KEYBINDINGS_CONFIG_VERSION {
+                    if let Err(e) = migrate_keybindings_file(&path, version, &config) {
+                        eprintln!("Warning: 設定ファイルの移行に失敗しました: {e}");
+                    }
+                }
+                config
+            }
+            Err(e) if load_settings().strict_config => {
+                // 厳格モード: デフォルトに戻さず、修正されるまで空の設定とする
+                set_config_error(&KEYBINDINGS_CONFIG_ERROR, Some(e));
+                Vec::new()
+            }
+            Err(e) => {
+                // ユーザーの編集内容を失わないよう、ファイルは上書きせずバックアップを残し、
+                // デフォルト設定はメモリ上でのみ使用する
+                // （保存のたびに更新される .bak とは別のファイルに残す）
+                let backup_path = with_file_suffix(&path, ".broken");
+                let backup = fs::copy(&path, &backup_path).map(|_| backup_path.as_path());
+                let message = load_settings()
+                    .language
+                    .resolve()
+                    .broken_file_message(&e, backup.as_ref().copied());
+                eprintln!("Warning: {message}");
+                set_config_error(&KEYBINDINGS_CONFIG_ERROR, Some(message.clone()));
+                if let Some(app) = APP_HANDLE.get() {
+                    let _ = app.emit("keybindings-parse-error", message);
+                }
+                get_default_keybindings()
+            }
+        }
+    } else {
+        set_config_error(&KEYBINDINGS_CONFIG_ERROR, None);
+        let default = get_default_keybindings();
+        let _ = write_keybindings_config(&default);
+        default
+    };
+
+    // キャッシュを更新
+    let config = Arc::new(config);
+    *write_cache(&KEYBINDINGS_CACHE) = Some(KeybindingsCache {
+        data: Arc::clone(&config),
+        last_modified: get_file_modified_time(&path),
+    });
+
+    // 前回読み込んだ内容との差分を通知
+    track_shortcuts_reload(&build_effective_config(Arc::clone(&config)));
+
     config
 }
 
-// キーバインド設定を保存
+/// ショートカットの識別子（アプリ名とアクション名）
+fn shortcut_id(shortcut: &NormalizedShortcut) -> (&str, &str) {
+    (&shortcut.app, &shortcut.action)
+}
+
+/// 2つのショートカット一覧の差分を計算
+fn diff_shortcuts(
+    previous: &[NormalizedShortcut],
+    current: &[NormalizedShortcut],
+) -> ShortcutsDiff {
+    let previous_by_id: HashMap<_, _> = previous.iter().map(|s| (shortcut_id(s), s)).collect();
+    let current_by_id: HashMap<_, _> = current.iter().map(|s| (shortcut_id(s), s)).collect();
+
+    ShortcutsDiff {
+        added: current
+            .iter()
+            .filter(|s| !previous_by_id.contains_key(&shortcut_id(s)))
+            .cloned()
+            .collect(),
+        removed: previous
+            .iter()
+            .filter(|s| !current_by_id.contains_key(&shortcut_id(s)))
+            .cloned()
+            .collect(),
+        changed: current
+            .iter()
+            .filter(|s| {
+                previous_by_id
+                    .get(&shortcut_id(s))
+                    .is_some_and(|previous| previous != s)
+            })
+            .cloned()
+            .collect(),
+    }
+}
+
+/// 読み込んだ設定を前回の内容と比較し、変化があれば "shortcuts-diff" イベントで通知
+fn track_shortcuts_reload(config: &[AppConfig]) {
+    let current = normalize_shortcuts(
+        config.iter().filter(|app| app.is_available()).cloned(),
+        load_settings().key_display_style,
+    );
+    let previous = LAST_SHORTCUTS
+        .lock()
+        .ok()
+        .and_then(|mut last| last.replace(current.clone()));
+    // 初回読み込み時は比較対象がないため通知しない
+    let Some(previous) = previous else {
+        return;
+    };
+
+    let diff = diff_shortcuts(&previous, &current);
+    if diff.is_empty() {
+        return;
+    }
+    if let Ok(mut last_diff) = LAST_SHORTCUTS_DIFF.lock() {
+        *last_diff = Some(diff.clone());
+    }
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit("shortcuts-diff", diff);
+    }
+}
+
+// 最後に検出したショートカットの差分を取得するコマンド
+#[tauri::command]
+fn get_last_shortcuts_diff() -> ShortcutsDiff {
+    LAST_SHORTCUTS_DIFF
+        .lock()
+        .ok()
+        .and_then(|last_diff| last_diff.clone())
+        .unwrap_or_default()
+}
+
+// 各キャッシュを事前に読み込むコマンド（初回呼び出しの遅延を避けるため、起動時にも実行）
+// 既にキャッシュ済みでファイルが更新されていなければ何もしないため、繰り返し呼び出しても安全
+#[tauri::command]
+fn warm_caches() {
+    load_settings();
+    // 正規化済みショートカット一覧（LAST_SHORTCUTS）も合わせて作成される
+    load_keybindings_config();
+    load_usage();
+    load_favorites();
+}
+
+// キーバインド設定を保存（編集・インポートなど、現在の設定を元にした保存用）
 fn save_keybindings_config(config: &Vec<AppConfig>) -> Result<(), String> {
+    ensure_keybindings_writable()?;
+    write_keybindings_config(config)
+}
+
+/// keybindings.json を読み込めているか確認
+/// パースエラー中の設定はデフォルト（厳格モードでは空）の代替のため、
+/// それを元に保存するとユーザーの設定が上書きされて失われる
+fn ensure_keybindings_writable() -> Result<(), String> {
+    // 最新の状態で判定するため先に読み込む
+    load_user_keybindings_config();
+    let error = KEYBINDINGS_CONFIG_ERROR
+        .lock()
+        .ok()
+        .and_then(|guard| guard.clone());
+    error.map_or(Ok(()), |e| {
+        Err(load_settings()
+            .language
+            .resolve()
+            .keybindings_not_writable_message(&e))
+    })
+}
+
+/// キーバインド設定をファイルに書き込む（読み込みエラーの有無は確認しない）
+/// デフォルト設定での作成やリセットなど、現在の内容に依存しない書き込みに使用
+fn write_keybindings_config(config: &Vec<AppConfig>) -> Result<(), String> {
     let path = get_keybindings_config_path().ok_or("設定ディレクトリが見つかりません")?;
 
     // ディレクトリを作成
@@ -410,31 +1533,211 @@ fn save_keybindings_config(config: &Vec<AppConfig>) -> Result<(), String> {
         fs::create_dir_all(parent).map_err(|e| format!("ディレクトリ作成エラー: {e}"))?;
     }
 
-    let json = serde_json::to_string_pretty(config).map_err(|e| format!("JSON変換エラー: {e}"))?;
-    fs::write(&path, json).map_err(|e| format!("ファイル書き込みエラー: {e}"))?;
+    let file = serde_json::json!({ "version": KEYBINDINGS_CONFIG_VERSION, "apps": config });
+    let content = serialize_config(&path, &file)?;
+    write_with_backup(&path, &content)?;
 
     // キャッシュをクリア（次回読み込み時に再取得）
-    if let Ok(mut cache_guard) = KEYBINDINGS_CACHE.lock() {
-        *cache_guard = None;
-    }
+    *write_cache(&KEYBINDINGS_CACHE) = None;
 
     Ok(())
 }
 
+/// ファイルを `write_atomically` で置き換え、置き換え前の内容は `.bak` として1世代分残す
+fn write_with_backup(path: &PathBuf, content: &str) -> Result<(), String> {
+    if path.exists() {
+        fs::copy(path, with_file_suffix(path, ".bak"))
+            .map_err(|e| format!("バックアップ作成エラー: {e}"))?;
+    }
+    write_atomically(path, content)
+}
+
+/// 一時ファイルに書き込んでから置き換える（書き込みに失敗しても元のファイルは残る）
+fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let temp_path = with_file_suffix(path, ".tmp");
+    if let Err(e) = fs::write(&temp_path, content) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("ファイル書き込みエラー: {e}"));
+    }
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("ファイル書き込みエラー: {e}")
+    })
+}
+
 // キャッシュ用の構造体
 struct KeybindingsCache {
-    data: Vec<AppConfig>,
+    data: Arc<Vec<AppConfig>>,
     last_modified: Option<SystemTime>,
 }
 
+// keybindings.d/ の設定やデフォルト設定と統合した結果（元になった設定の Arc を保持して比較に使う）
+struct EffectiveKeybindingsCache {
+    user: Arc<Vec<AppConfig>>,
+    extra: Arc<Vec<AppConfig>>,
+    merge_defaults: bool,
+    data: Arc<Vec<AppConfig>>,
+}
+
 struct SettingsCache {
     data: AppSettings,
     last_modified: Option<SystemTime>,
 }
 
+// 補助データ（usage.json など）用の汎用キャッシュ
+struct JsonStoreCache<T> {
+    data: T,
+    last_modified: Option<SystemTime>,
+    // パースできなかった場合のエラー（解消されるまで保存を拒否する）
+    error: Option<String>,
+}
+
 // キャッシュ
-static KEYBINDINGS_CACHE: Mutex<Option<KeybindingsCache>> = Mutex::new(None);
-static SETTINGS_CACHE: Mutex<Option<SettingsCache>> = Mutex::new(None);
+// 設定ファイルのキャッシュは読み込みが大半のため RwLock とし、キャッシュヒット時は読み取りロックのみ取得する
+static KEYBINDINGS_CACHE: RwLock<Option<KeybindingsCache>> = RwLock::new(None);
+static SETTINGS_CACHE: RwLock<Option<SettingsCache>> = RwLock::new(None);
+// keybindings.d/ のキャッシュ（ファイルごとの更新時刻と連結した設定）
+type KeybindingsDirCache = (Vec<(PathBuf, Option<SystemTime>)>, Arc<Vec<AppConfig>>);
+static KEYBINDINGS_DIR_CACHE: RwLock<Option<KeybindingsDirCache>> = RwLock::new(None);
+static EFFECTIVE_KEYBINDINGS_CACHE: RwLock<Option<EffectiveKeybindingsCache>> = RwLock::new(None);
+
+/// キャッシュの読み取りロックを取得
+/// 他のスレッドがロック中にパニックしていても、中身を回復して使い続ける（デフォルト設定に戻り続けないように）
+fn read_cache<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        eprintln!("Warning: キャッシュのロックが汚染されていたため回復しました");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// キャッシュの書き込みロックを取得（汚染されていた場合は `read_cache` と同様に回復）
+fn write_cache<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        eprintln!("Warning: キャッシュのロックが汚染されていたため回復しました");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+static USAGE_CACHE: Mutex<Option<JsonStoreCache<Vec<UsageEntry>>>> = Mutex::new(None);
+static FAVORITES_CACHE: Mutex<Option<JsonStoreCache<Vec<FavoriteEntry>>>> = Mutex::new(None);
+
+// 最近マッチしたアプリとその時刻（メモリ上のみ）
+static RECENT_APPS: Mutex<Vec<(NormalizedApp, SystemTime)>> = Mutex::new(Vec::new());
+
+// 前回読み込んだショートカット一覧と、その後に検出した差分
+static LAST_SHORTCUTS: Mutex<Option<Vec<NormalizedShortcut>>> = Mutex::new(None);
+static LAST_SHORTCUTS_DIFF: Mutex<Option<ShortcutsDiff>> = Mutex::new(None);
+
+// 設定ファイルのエラー
+// keybindings.json は常に記録し、解消されるまで保存を拒否する（settings.json は厳格モードのみ）
+static KEYBINDINGS_CONFIG_ERROR: Mutex<Option<String>> = Mutex::new(None);
+static SETTINGS_CONFIG_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// 補助データのJSONファイルを読み込む（キャッシュ付き）
+/// ファイルが存在しない、またはパースできない場合はデフォルト値を返す（ファイルは作成しない）
+fn load_json_store<T>(path: Option<PathBuf>, cache: &Mutex<Option<JsonStoreCache<T>>>) -> T
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    let Some(path) = path else {
+        return T::default();
+    };
+    let mut cache_guard = cache
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    read_json_store(&path, &mut cache_guard).unwrap_or_default()
+}
+
+/// 補助データを読み込み、変更してJSONファイルに保存する
+/// 同時に更新しても互いの変更を失わないよう、読み込みから保存までキャッシュのロックを保持する
+fn update_json_store<T, R>(
+    path: Option<PathBuf>,
+    cache: &Mutex<Option<JsonStoreCache<T>>>,
+    update: impl FnOnce(&mut T) -> R,
+) -> Result<R, String>
+where
+    T: Clone + Default + DeserializeOwned + Serialize,
+{
+    let path = path.ok_or("設定ディレクトリが見つかりません")?;
+    let mut cache_guard = cache
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    // パースできないファイルを上書きすると元のデータを失うため、修正されるまで保存しない
+    let mut data = read_json_store(&path, &mut cache_guard)?;
+    let result = update(&mut data);
+
+    // ディレクトリを作成
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("ディレクトリ作成エラー: {e}"))?;
+    }
+
+    let json = serde_json::to_string_pretty(&data).map_err(|e| format!("JSON変換エラー: {e}"))?;
+    write_atomically(&path, &json)?;
+
+    // キャッシュを更新
+    *cache_guard = Some(JsonStoreCache {
+        data,
+        last_modified: get_file_modified_time(&path),
+        error: None,
+    });
+    drop(cache_guard);
+    Ok(result)
+}
+
+/// キャッシュの内容を返す（ファイルのタイムスタンプが変わっていれば読み込み直す）
+/// パースできない場合は .broken にバックアップを残してエラーを返す
+fn read_json_store<T>(path: &PathBuf, cache: &mut Option<JsonStoreCache<T>>) -> Result<T, String>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    let current_modified = get_file_modified_time(path);
+
+    // タイムスタンプが同じならキャッシュを返す
+    if let Some(cached) = cache
+        .as_ref()
+        .filter(|cached| cached.last_modified == current_modified)
+    {
+        return cached
+            .error
+            .clone()
+            .map_or_else(|| Ok(cached.data.clone()), Err);
+    }
+
+    // ファイルを読み込む（存在しない場合は空のデータとする）
+    let (data, error) = match fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str::<T>(&content) {
+            Ok(data) => (data, None),
+            Err(e) => {
+                let language = load_settings().language.resolve();
+                let backup_path = with_file_suffix(path, ".broken");
+                let backup = fs::copy(path, &backup_path).map(|_| backup_path.as_path());
+                let message = language.broken_file_message(
+                    &language.parse_error_message(path, &e.to_string()),
+                    backup.as_ref().copied(),
+                );
+                eprintln!("Warning: {message}");
+                (T::default(), Some(message))
+            }
+        },
+        Err(_) => (T::default(), None),
+    };
+    *cache = Some(JsonStoreCache {
+        data: data.clone(),
+        last_modified: current_modified,
+        error: error.clone(),
+    });
+    error.map_or(Ok(data), Err)
+}
+
+// アプリケーションハンドル（コマンド以外の処理からイベントを送信する用）
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+// アクティブウィンドウの監視スレッドと停止フラグ
+static MONITOR_THREAD: Mutex<Option<thread::JoinHandle<()>>> = Mutex::new(None);
+static MONITOR_STOP: AtomicBool = AtomicBool::new(false);
+// アクティブウィンドウの監視が有効かどうか（無効の間はスレッドを残したまま処理を省略）
+static MONITOR_ENABLED: AtomicBool = AtomicBool::new(true);
 
 // 前回アクティブだったアプリ情報を保持
 static LAST_ACTIVE_APP: Mutex<Option<ActiveWindowInfo>> = Mutex::new(None);
@@ -443,33 +1746,66 @@ static LAST_ACTIVE_APP: Mutex<Option<ActiveWindowInfo>> = Mutex::new(None);
 static LAST_ACTIVE_HWND: Mutex<Option<isize>> = Mutex::new(None);
 // ウィンドウが表示中かどうか
 static WINDOW_VISIBLE: AtomicBool = AtomicBool::new(false);
+// 最後に送信したwindow-shownイベントのペイロード（フロントエンドのリロード時の再同期用）
+static LAST_SHOWN_PAYLOAD: Mutex<Option<WindowShownPayload>> = Mutex::new(None);
+// メインウィンドウが固定表示中かどうか（フォーカスを失っても非表示にしない）
+static WINDOW_PINNED: AtomicBool = AtomicBool::new(false);
+// オーバーレイが固定表示中かどうか（タイマーで非表示にしない）
+static OVERLAY_PINNED: PinFlag = PinFlag::new();
+// オーバーレイを表示した回数（前回の表示のタイマーが新しい表示を閉じないようにする）
+static OVERLAY_GENERATION: AtomicU64 = AtomicU64::new(0);
+// 登録できなかった起動ホットキーのエラー
+static HOTKEY_ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+// システムトレイのID
+const TRAY_ID: &str = "main";
+// トレイメニューの検出中アプリ項目のIDの接頭辞
+const MATCHED_APP_MENU_PREFIX: &str = "matched-app:";
+// トレイメニューに表示中の検出アプリ名（変化した時のみメニューを作り直す）
+static TRAY_MATCHED_APPS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+// 保存待ちのメインウィンドウの位置とサイズ（連続する移動イベントをまとめて保存）
+static PENDING_WINDOW_BOUNDS: Mutex<Option<WindowBounds>> = Mutex::new(None);
+static WINDOW_BOUNDS_SAVE_SCHEDULED: AtomicBool = AtomicBool::new(false);
+// アプリ自身が最後に配置したメインウィンドウの位置とサイズ
+static APPLIED_WINDOW_BOUNDS: Mutex<Option<WindowBounds>> = Mutex::new(None);
 
 #[cfg(target_os = "windows")]
 mod active_window {
-    use super::{ActiveWindowInfo, LAST_ACTIVE_HWND};
+    use super::{is_app_matched, ActiveWindowInfo, AppConfig, LAST_ACTIVE_HWND};
     use windows::Win32::Foundation::CloseHandle;
-    use windows::Win32::Foundation::HWND;
-    use windows::Win32::System::ProcessStatus::GetModuleBaseNameW;
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows::Win32::System::ProcessStatus::{GetModuleBaseNameW, GetModuleFileNameExW};
     use windows::Win32::System::Threading::{
         GetCurrentProcessId, OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
     };
     use windows::Win32::UI::WindowsAndMessaging::{
-        GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
-        SetForegroundWindow,
+        EnumWindows, GetAncestor, GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW,
+        GetWindowThreadProcessId, IsIconic, IsWindow, IsWindowVisible, SetForegroundWindow,
+        ShowWindow, GA_ROOTOWNER, SW_RESTORE,
     };
 
-    /// アクティブなウィンドウの情報を取得（自分自身を除外）
-    #[allow(unsafe_code)]
-    #[allow(clippy::cast_sign_loss)] // Windows APIの戻り値は正の値（len > 0チェック済み）
+    /// アクティブなウィンドウの情報を取得し、フォーカス復元用にHWNDを保存（自分自身を除外）
     pub fn get_active_window_info() -> Option<ActiveWindowInfo> {
+        let (hwnd, info) = read_foreground_window()?;
+        if let Ok(mut last_hwnd) = LAST_ACTIVE_HWND.lock() {
+            *last_hwnd = Some(hwnd);
+        }
+        Some(info)
+    }
+
+    /// アクティブなウィンドウの情報を取得（HWNDは保存しない、診断用）
+    pub fn peek_active_window_info() -> Option<ActiveWindowInfo> {
+        read_foreground_window().map(|(_, info)| info)
+    }
+
+    /// 最前面のウィンドウのHWNDと情報を取得（自分自身を除外）
+    /// ダイアログなど所有者のいるウィンドウの場合は、所有者の最上位ウィンドウの情報も含める
+    #[allow(unsafe_code)]
+    fn read_foreground_window() -> Option<(isize, ActiveWindowInfo)> {
         // SAFETY: 以下のWindows API呼び出しは安全です：
         // - GetForegroundWindow: 常に有効なHWNDまたはNULLを返す
         // - GetWindowThreadProcessId: 有効なHWNDに対してプロセスIDを取得
         // - GetCurrentProcessId: 常に現在のプロセスIDを返す
-        // - OpenProcess: 失敗時はエラーを返し、成功時は有効なハンドル
-        // - GetModuleBaseNameW: バッファサイズを指定して安全に呼び出し
-        // - CloseHandle: OpenProcessで取得したハンドルを正しく解放
-        // - GetWindowTextW: バッファサイズを事前に取得し、オーバーフローを防止
+        // - GetAncestor: 無効なHWNDに対してはNULLを返す
         unsafe {
             let hwnd: HWND = GetForegroundWindow();
             if hwnd.0.is_null() {
@@ -489,13 +1825,34 @@ mod active_window {
                 return None;
             }
 
-            // HWNDを保存
-            if let Ok(mut last_hwnd) = LAST_ACTIVE_HWND.lock() {
-                *last_hwnd = Some(hwnd.0 as isize);
+            let mut info = read_window_info(hwnd, process_id);
+
+            // 所有者の最上位ウィンドウ（ダイアログの親アプリなど）
+            let root_owner = GetAncestor(hwnd, GA_ROOTOWNER);
+            if !root_owner.0.is_null() && root_owner != hwnd {
+                let mut owner_process_id: u32 = 0;
+                GetWindowThreadProcessId(root_owner, Some(&raw mut owner_process_id));
+                if owner_process_id != 0 && owner_process_id != current_pid {
+                    info.owner = Some(Box::new(read_window_info(root_owner, owner_process_id)));
+                }
             }
 
-            // プロセス名を取得
-            let process_name = {
+            Some((hwnd.0 as isize, info))
+        }
+    }
+
+    /// ウィンドウのプロセス名・実行ファイルのパス・タイトルを取得
+    #[allow(unsafe_code)]
+    #[allow(clippy::cast_sign_loss)] // Windows APIの戻り値は正の値（len > 0チェック済み）
+    fn read_window_info(hwnd: HWND, process_id: u32) -> ActiveWindowInfo {
+        // SAFETY: 以下のWindows API呼び出しは安全です：
+        // - OpenProcess: 失敗時はエラーを返し、成功時は有効なハンドル
+        // - GetModuleBaseNameW / GetModuleFileNameExW: バッファサイズを指定して安全に呼び出し
+        // - CloseHandle: OpenProcessで取得したハンドルを正しく解放
+        // - GetWindowTextW: バッファサイズを事前に取得し、オーバーフローを防止
+        unsafe {
+            // プロセス名と実行ファイルのパスを取得
+            let (process_name, executable) = {
                 let process_handle = OpenProcess(
                     PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
                     false,
@@ -503,10 +1860,10 @@ mod active_window {
                 )
                 .ok();
 
-                process_handle.and_then(|handle| {
+                process_handle.map_or((None, None), |handle| {
                     let mut buffer = [0u16; 260];
                     let len = GetModuleBaseNameW(handle, None, &mut buffer);
-                    let result = if len == 0 {
+                    let name = if len == 0 {
                         None
                     } else {
                         let name = String::from_utf16_lossy(&buffer[..len as usize]);
@@ -516,9 +1873,16 @@ mod active_window {
                                 .to_string(),
                         )
                     };
+                    let mut path_buffer = [0u16; 1024];
+                    let path_len = GetModuleFileNameExW(handle, None, &mut path_buffer);
+                    let path = if path_len == 0 {
+                        None
+                    } else {
+                        Some(String::from_utf16_lossy(&path_buffer[..path_len as usize]))
+                    };
                     // プロセスハンドルを閉じる
                     let _ = CloseHandle(handle);
-                    result
+                    (name, path)
                 })
             };
 
@@ -538,22 +1902,80 @@ mod active_window {
                 }
             };
 
-            Some(ActiveWindowInfo {
+            ActiveWindowInfo {
                 process: process_name,
                 window: window_title,
-            })
+                executable,
+                owner: None,
+            }
+        }
+    }
+
+    /// アプリのバインドにマッチする表示中のウィンドウを最前面に表示（自分自身を除外）
+    /// 最小化されている場合は元に戻す。マッチするウィンドウがなければ false
+    #[allow(unsafe_code)]
+    pub fn focus_app_window(app: &AppConfig) -> bool {
+        let mut windows: Vec<HWND> = Vec::new();
+        // SAFETY: EnumWindowsのコールバックには呼び出し中のみ有効な windows へのポインタを渡す。
+        // GetWindowThreadProcessId / IsIconic / ShowWindow / SetForegroundWindow は
+        // 列挙中に閉じられた無効なHWNDに対しても安全に失敗する
+        unsafe {
+            let _ = EnumWindows(
+                Some(collect_visible_window),
+                LPARAM(&raw mut windows as isize),
+            );
+            let current_pid = GetCurrentProcessId();
+            for hwnd in windows {
+                let mut process_id: u32 = 0;
+                GetWindowThreadProcessId(hwnd, Some(&raw mut process_id));
+                if process_id == 0 || process_id == current_pid {
+                    continue;
+                }
+                if is_app_matched(&read_window_info(hwnd, process_id), app) {
+                    if IsIconic(hwnd).as_bool() {
+                        let _ = ShowWindow(hwnd, SW_RESTORE);
+                    }
+                    return SetForegroundWindow(hwnd).as_bool();
+                }
+            }
+        }
+        false
+    }
+
+    /// `EnumWindows` のコールバック（表示中のトップレベルウィンドウを集める）
+    #[allow(unsafe_code)]
+    unsafe extern "system" fn collect_visible_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: lparam は focus_app_window の windows へのポインタ
+        let windows = unsafe { &mut *(lparam.0 as *mut Vec<HWND>) };
+        if unsafe { IsWindowVisible(hwnd) }.as_bool() {
+            windows.push(hwnd);
         }
+        BOOL(1)
     }
 
     /// 保存されたHWNDのウィンドウにフォーカスを戻す
+    /// ウィンドウが既に閉じられている場合は保存値をクリアし、復元しない
     #[allow(unsafe_code)]
     pub fn restore_focus_to_last_window() {
-        if let Ok(last_hwnd) = LAST_ACTIVE_HWND.lock() {
+        if let Ok(mut last_hwnd) = LAST_ACTIVE_HWND.lock() {
             if let Some(hwnd_val) = *last_hwnd {
-                // SAFETY: SetForegroundWindowは無効なHWNDでも安全に失敗する
-                // HWNDはget_active_window_infoで取得した有効なウィンドウハンドル
+                // SAFETY: IsWindow / GetWindowThreadProcessId / SetForegroundWindow は
+                // 無効なHWNDに対しても安全に失敗する
                 unsafe {
                     let hwnd = HWND(hwnd_val as *mut std::ffi::c_void);
+                    if !IsWindow(hwnd).as_bool() {
+                        // 閉じられたウィンドウのHWNDは再利用される可能性があるため破棄
+                        *last_hwnd = None;
+                        return;
+                    }
+
+                    // 自分自身のウィンドウにはフォーカスを戻さない
+                    let mut process_id: u32 = 0;
+                    GetWindowThreadProcessId(hwnd, Some(&raw mut process_id));
+                    if process_id == GetCurrentProcessId() {
+                        return;
+                    }
+
                     let _ = SetForegroundWindow(hwnd);
                 }
             }
@@ -563,802 +1985,5600 @@ mod active_window {
 
 #[cfg(target_os = "macos")]
 mod active_window {
-    use super::ActiveWindowInfo;
+    use super::{ActiveWindowInfo, AppConfig};
     /// macOS: ダミー実装
     pub fn get_active_window_info() -> Option<ActiveWindowInfo> {
         None
     }
     /// macOS: ダミー実装
+    pub fn peek_active_window_info() -> Option<ActiveWindowInfo> {
+        None
+    }
+    /// macOS: ダミー実装
     pub fn restore_focus_to_last_window() {}
+    /// バインドをアプリ名として、起動中であれば AppleScript の activate で最前面に表示
+    /// activate は起動していないアプリも起動してしまうため、先に起動中か確認する
+    /// （起動していなければ false を返し、launch の設定で起動させる）
+    pub fn focus_app_window(app: &AppConfig) -> bool {
+        app.get_binds().iter().any(|bind| {
+            let name = bind.replace('\\', "\\\\").replace('"', "\\\"");
+            let script = format!(
+                "if application \"{name}\" is running then\n\
+                 tell application \"{name}\" to activate\n\
+                 return true\n\
+                 end if\n\
+                 return false"
+            );
+            std::process::Command::new("osascript")
+                .arg("-e")
+                .arg(script)
+                .output()
+                .is_ok_and(|output| {
+                    output.status.success()
+                        && String::from_utf8_lossy(&output.stdout).trim() == "true"
+                })
+        })
+    }
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "macos")))]
 mod active_window {
-    use super::ActiveWindowInfo;
+    use super::{ActiveWindowInfo, AppConfig};
     /// その他のOS: ダミー実装
     pub fn get_active_window_info() -> Option<ActiveWindowInfo> {
         None
     }
     /// その他のOS: ダミー実装
+    pub fn peek_active_window_info() -> Option<ActiveWindowInfo> {
+        None
+    }
+    /// その他のOS: ダミー実装
     pub fn restore_focus_to_last_window() {}
+    /// その他のOS: ダミー実装
+    pub fn focus_app_window(_app: &AppConfig) -> bool {
+        false
+    }
 }
 
-// 前回のアクティブアプリを更新する
-fn update_last_active_app() {
-    if let Some(info) = active_window::get_active_window_info() {
-        if let Ok(mut last_app) = LAST_ACTIVE_APP.lock() {
-            *last_app = Some(info);
+#[cfg(target_os = "windows")]
+mod app_icon {
+    use std::path::Path;
+    use windows::core::HSTRING;
+    use windows::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::UI::Shell::ExtractIconExW;
+    use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, HICON, ICONINFO};
+
+    /// 実行ファイルのアイコンを抽出してPNGとして保存
+    #[allow(unsafe_code)]
+    pub fn extract_icon_png(executable: &str, dest: &Path) -> Result<(), String> {
+        // SAFETY: ExtractIconExWは取得できなかった場合に0を返す。
+        // 取得したアイコンとビットマップは使用後に必ず解放する
+        let (width, height, mut pixels) = unsafe {
+            let mut icon = HICON::default();
+            let count = ExtractIconExW(&HSTRING::from(executable), 0, Some(&raw mut icon), None, 1);
+            if count == 0 || icon.is_invalid() {
+                return Err(format!("アイコンが見つかりません: {executable}"));
+            }
+
+            let mut icon_info = ICONINFO::default();
+            let result = GetIconInfo(icon, &raw mut icon_info)
+                .map_err(|e| format!("アイコン情報の取得に失敗しました: {e}"))
+                .and_then(|()| read_color_bitmap(&icon_info));
+            let _ = DeleteObject(icon_info.hbmColor);
+            let _ = DeleteObject(icon_info.hbmMask);
+            let _ = DestroyIcon(icon);
+            result?
+        };
+
+        // BGRA → RGBA に変換（アルファ値を持たない古い形式のアイコンは不透明として扱う）
+        let has_alpha = pixels.chunks_exact(4).any(|pixel| pixel[3] != 0);
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            if !has_alpha {
+                pixel[3] = 255;
+            }
         }
+
+        let file = std::fs::File::create(dest)
+            .map_err(|e| format!("アイコンファイルの作成に失敗しました: {e}"))?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| format!("PNGの書き込みに失敗しました: {e}"))
     }
-}
 
-// 前回のアクティブアプリ情報を取得する
-fn get_last_active_app() -> Option<ActiveWindowInfo> {
-    LAST_ACTIVE_APP.lock().ok()?.clone()
-}
+    /// アイコンのカラービットマップを32bit（BGRA、トップダウン）で読み込む
+    #[allow(unsafe_code)]
+    #[allow(clippy::cast_sign_loss)] // 幅・高さは正の値（0以下はエラーとして除外済み）
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)] // 構造体サイズは小さな定数
+    fn read_color_bitmap(icon_info: &ICONINFO) -> Result<(u32, u32, Vec<u8>), String> {
+        // SAFETY: hbmColorはGetIconInfoで取得したビットマップ（モノクロアイコンではNULLで、
+        // その場合GetObjectWが0を返す）。GetDIBitsにはビットマップの大きさ分のバッファを渡す
+        unsafe {
+            let mut bitmap = BITMAP::default();
+            let size = GetObjectW(
+                icon_info.hbmColor,
+                std::mem::size_of::<BITMAP>() as i32,
+                Some((&raw mut bitmap).cast()),
+            );
+            if size == 0 || bitmap.bmWidth <= 0 || bitmap.bmHeight <= 0 {
+                return Err("アイコンのビットマップを取得できません".to_string());
+            }
 
-// ウィンドウの表示/非表示を切り替え
-fn toggle_window(app: &AppHandle) {
-    if let Some(window) = app.get_webview_window("search") {
-        if window.is_visible().unwrap_or(false) {
-            WINDOW_VISIBLE.store(false, Ordering::SeqCst);
-            let _ = window.hide();
-        } else {
-            // 保存しておいた前回のアクティブアプリを使用
-            let active_app = get_last_active_app();
+            let width = bitmap.bmWidth as u32;
+            let height = bitmap.bmHeight as u32;
+            let mut bitmap_info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: bitmap.bmWidth,
+                    // 負の値でトップダウン形式になる
+                    biHeight: -bitmap.bmHeight,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut pixels = vec![0u8; (width * height * 4) as usize];
 
-            WINDOW_VISIBLE.store(true, Ordering::SeqCst);
-            let _ = window.center();
-            let _ = window.show();
-            let _ = window.set_focus();
-            // フロントエンドに通知（アクティブアプリ名を含む）
-            let _ = window.emit("window-shown", active_app);
+            let hdc = GetDC(None);
+            let lines = GetDIBits(
+                hdc,
+                icon_info.hbmColor,
+                0,
+                height,
+                Some(pixels.as_mut_ptr().cast()),
+                &raw mut bitmap_info,
+                DIB_RGB_COLORS,
+            );
+            let _ = ReleaseDC(None, hdc);
+
+            if lines == 0 {
+                return Err("アイコンのピクセルデータを取得できません".to_string());
+            }
+            Ok((width, height, pixels))
         }
     }
 }
 
-// ウィンドウを非表示
-fn hide_window(app: &AppHandle) {
-    if let Some(window) = app.get_webview_window("search") {
-        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
-        let _ = window.hide();
-        let _ = window.emit("window-hidden", ());
+#[cfg(not(target_os = "windows"))]
+mod app_icon {
+    use std::path::Path;
+    /// Windows以外: 未対応
+    pub fn extract_icon_png(_executable: &str, _dest: &Path) -> Result<(), String> {
+        Err("アイコンの自動取得はこのOSでは未対応です".to_string())
     }
 }
 
-// バックグラウンドでアクティブウィンドウを監視するスレッドを開始
-fn start_active_window_monitor() {
-    thread::spawn(|| {
-        loop {
-            let visible = WINDOW_VISIBLE.load(Ordering::SeqCst);
-            // ウィンドウが非表示の時だけアクティブアプリを更新
-            if !visible {
-                update_last_active_app();
-            }
-            // 200msごとに監視
-            thread::sleep(Duration::from_millis(200));
-        }
-    });
-}
+#[cfg(target_os = "windows")]
+mod installed_apps {
+    use windows::core::{w, PWSTR};
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE,
+        KEY_READ,
+    };
 
-// ウィンドウを非表示にするコマンド
-#[tauri::command]
-fn hide_main_window(app: AppHandle) {
-    hide_window(&app);
-}
-
-// プラットフォームを取得するコマンド
-#[tauri::command]
-fn get_platform() -> String {
-    if cfg!(target_os = "macos") {
-        "darwin".to_string()
-    } else if cfg!(target_os = "windows") {
-        "win32".to_string()
-    } else {
-        "linux".to_string()
-    }
-}
-
-/// アクティブウィンドウにマッチするアプリを検索
-/// プロセス名またはウィンドウタイトルで完全一致（大文字小文字無視）
-fn match_apps(info: &ActiveWindowInfo, apps: &[AppConfig]) -> Vec<NormalizedApp> {
-    apps.iter()
-        .filter_map(|app| {
-            let binds = app.get_binds();
-            let mut matched = false;
-
-            for bind in &binds {
-                // プロセス名で完全一致
-                if let Some(ref info_process) = info.process {
-                    if info_process.to_lowercase() == bind.to_lowercase() {
-                        matched = true;
-                        break;
-                    }
+    /// App Paths レジストリのサブキー名（例: "chrome.exe"）を列挙（HKLM と HKCU）
+    #[allow(unsafe_code)]
+    #[allow(clippy::cast_possible_truncation)] // バッファ長は256なのでu32に収まる
+    pub fn list_app_path_keys() -> Vec<String> {
+        let mut names = Vec::new();
+        for root in [HKEY_LOCAL_MACHINE, HKEY_CURRENT_USER] {
+            // SAFETY: RegOpenKeyExWで開いたキーのみ列挙し、使用後にRegCloseKeyで閉じる。
+            // RegEnumKeyExWにはバッファ長を渡し、返された長さの範囲のみ読み取る
+            unsafe {
+                let mut key = HKEY::default();
+                let opened = RegOpenKeyExW(
+                    root,
+                    w!("SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\App Paths"),
+                    0,
+                    KEY_READ,
+                    &raw mut key,
+                );
+                if opened != ERROR_SUCCESS {
+                    continue;
                 }
 
-                // ウィンドウタイトルで完全一致
-                if let Some(ref info_window) = info.window {
-                    if info_window.to_lowercase() == bind.to_lowercase() {
-                        matched = true;
+                let mut index = 0;
+                loop {
+                    let mut buffer = [0u16; 256];
+                    let mut len = buffer.len() as u32;
+                    let result = RegEnumKeyExW(
+                        key,
+                        index,
+                        PWSTR(buffer.as_mut_ptr()),
+                        &raw mut len,
+                        None,
+                        PWSTR::null(),
+                        None,
+                        None,
+                    );
+                    if result != ERROR_SUCCESS {
                         break;
                     }
+                    names.push(String::from_utf16_lossy(&buffer[..len as usize]));
+                    index += 1;
                 }
+                let _ = RegCloseKey(key);
             }
+        }
+        names
+    }
+}
 
-            if matched {
-                Some(NormalizedApp {
-                    name: app.get_name(),
-                    icon: app.get_icon(),
-                })
-            } else {
-                None
-            }
+#[cfg(not(target_os = "windows"))]
+mod installed_apps {
+    /// Windows以外: 空のリストを返す
+    pub const fn list_app_path_keys() -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// App Paths のサブキー名から実行ファイルのベース名を作成（拡張子を除去、重複を除外して名前順）
+fn app_names_from_app_paths(keys: Vec<String>) -> Vec<String> {
+    let mut names: Vec<String> = keys
+        .iter()
+        .map(|key| {
+            key.trim()
+                .trim_end_matches(".exe")
+                .trim_end_matches(".EXE")
+                .to_string()
         })
-        .collect()
+        .filter(|name| !name.is_empty())
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    names
 }
 
-// マッチしたアプリ情報を取得するコマンド
+// インストール済みアプリの実行ファイル名一覧を取得するコマンド（bind の候補用、Windowsのみ）
 #[tauri::command]
-fn get_matched_apps(info: Option<ActiveWindowInfo>) -> Vec<NormalizedApp> {
-    let config = load_keybindings_config();
-    info.as_ref()
-        .map_or_else(Vec::new, |window_info| match_apps(window_info, &config))
+fn list_installed_apps() -> Vec<String> {
+    app_names_from_app_paths(installed_apps::list_app_path_keys())
 }
 
-// ショートカット一覧を取得するコマンド
-#[tauri::command]
-fn get_shortcuts() -> Vec<NormalizedShortcut> {
-    let config = load_keybindings_config();
+/// 実行ファイルごとのアイコンキャッシュのファイル名を生成（例: code-0123456789abcdef.png）
+fn icon_cache_file_name(executable: &str) -> Option<String> {
+    let stem = Path::new(executable)
+        .file_stem()?
+        .to_string_lossy()
+        .to_lowercase();
+    // 同名の実行ファイルを区別するためフルパスのハッシュを付与
+    // （DefaultHasher はRustの更新で値が変わりキャッシュが使われなくなるため、FNV-1a で計算）
+    let hash = fnv1a_hash(executable.to_lowercase().as_bytes());
+    Some(format!("{stem}-{hash:016x}.png"))
+}
 
-    config
-        .into_iter()
-        // 現在のプラットフォームで有効なアプリのみ
-        .filter(AppConfig::is_available)
-        .flat_map(|app| {
-            let app_name = app.get_name();
-            let app_icon = app.get_icon();
-            app.keybindings.into_iter().filter_map(move |kb| {
-                // キーが"-"の場合は対象外
-                if kb.key == "-" {
-                    return None;
-                }
-                // 表示用に正規化（スペースあり形式に統一）
-                let key = normalize_key_for_display(&kb.key);
-                // 順次入力キーの区切り文字を変換: "->" → "→"
-                let key = key.replace(" -> ", " → ");
-                Some(NormalizedShortcut {
-                    app: app_name.clone(),
-                    icon: app_icon.clone(),
-                    action: kb.action,
-                    key,
-                    tags: kb.tags,
-                })
-            })
-        })
-        .collect()
+/// FNV-1a（64ビット）ハッシュ
+fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
-/// ファイルをシステムのデフォルトアプリケーションで開く
-fn open_file_with_default_app(path: &std::path::Path) -> Result<(), String> {
-    #[cfg(target_os = "windows")]
-    {
-        // explorer.exe でファイルを開く（パスのエスケープが不要）
-        std::process::Command::new("explorer")
-            .arg(path)
-            .spawn()
-            .map_err(|e| format!("ファイルを開けませんでした: {e}"))?;
+/// 自動取得したアイコンのパスを返す（未キャッシュなら抽出、失敗時はNoneで設定のアイコンを使用）
+/// 抽出には時間がかかるため、ウィンドウの表示処理からは呼び出さない
+fn resolve_auto_icon(executable: &str) -> Option<String> {
+    resolve_auto_icon_in(&get_config_dir()?.join("icons"), executable)
+}
+
+/// 指定したディレクトリをキャッシュとして、自動取得したアイコンのパスを返す
+fn resolve_auto_icon_in(icons_dir: &Path, executable: &str) -> Option<String> {
+    let path = icons_dir.join(icon_cache_file_name(executable)?);
+    if !path.exists() {
+        fs::create_dir_all(icons_dir).ok()?;
+        if app_icon::extract_icon_png(executable, &path).is_err() {
+            // 書き込み途中のファイルが残らないように削除
+            let _ = fs::remove_file(&path);
+            return None;
+        }
     }
+    Some(path.to_string_lossy().into_owned())
+}
 
-    #[cfg(target_os = "macos")]
+/// キャッシュ済みのアイコンを data URL で返す（抽出はしない）
+fn cached_auto_icon_url(executable: &str) -> Option<String> {
+    cached_auto_icon_url_in(&get_config_dir()?.join("icons"), executable)
+}
+
+/// 指定したディレクトリにキャッシュ済みのアイコンを data URL で返す
+fn cached_auto_icon_url_in(icons_dir: &Path, executable: &str) -> Option<String> {
+    let png = fs::read(icons_dir.join(icon_cache_file_name(executable)?)).ok()?;
+    Some(format!(
+        "data:image/png;base64,{}",
+        BASE64_STANDARD.encode(png)
+    ))
+}
+
+/// マッチした `auto_icon` のアプリがあれば、実行ファイルのアイコンを抽出してキャッシュする
+fn cache_auto_icons(info: &ActiveWindowInfo, apps: &[AppConfig]) {
+    let (info, matched) = matched_app_configs(info, apps);
+    if let Some(executable) = info
+        .executable
+        .as_deref()
+        .filter(|_| matched.iter().any(|app| app.auto_icon))
     {
-        std::process::Command::new("open")
-            .arg(path)
-            .spawn()
-            .map_err(|e| format!("ファイルを開けませんでした: {e}"))?;
+        let _ = resolve_auto_icon(executable);
     }
+}
 
-    #[cfg(target_os = "linux")]
+// 前回のアクティブアプリを更新する
+// 変更があった場合は true を返す
+fn update_last_active_app() -> bool {
+    let Some(mut info) = active_window::get_active_window_info() else {
+        return false;
+    };
+    // 所有者ウィンドウでのマッチングが無効なら所有者の情報は持たない
+    if !load_settings().match_owner_window {
+        info.owner = None;
+    }
     {
-        std::process::Command::new("xdg-open")
-            .arg(path)
-            .spawn()
-            .map_err(|e| format!("ファイルを開けませんでした: {e}"))?;
+        let Ok(mut last_app) = LAST_ACTIVE_APP.lock() else {
+            return false;
+        };
+        if last_app.as_ref() == Some(&info) {
+            return false;
+        }
+        *last_app = Some(info.clone());
     }
+    // 新しいアクティブウィンドウにマッチしたアプリを最近使ったアプリとして記録
+    let config = load_keybindings_config();
+    record_recent_apps(&match_apps(&info, &config));
+    // アイコンの抽出は時間がかかるため、表示時ではなく監視スレッドで済ませておく
+    cache_auto_icons(&info, &config);
+    true
+}
 
-    Ok(())
+/// トレイのツールチップ文字列を作成（例: "Finkey — active: Code / main.rs - finkey"）
+fn format_tray_tooltip(info: Option<&ActiveWindowInfo>, language: Language) -> String {
+    // Windowsのツールチップは128文字までのため、ウィンドウタイトルは短縮する
+    const MAX_TITLE_CHARS: usize = 60;
+    let process = info.and_then(|i| i.process.as_deref()).unwrap_or("-");
+    let window = info.and_then(|i| i.window.as_deref()).unwrap_or("-");
+    let window = if window.chars().count() > MAX_TITLE_CHARS {
+        let truncated: String = window.chars().take(MAX_TITLE_CHARS).collect();
+        format!("{truncated}…")
+    } else {
+        window.to_string()
+    };
+    language.tray_tooltip(process, &window)
 }
 
-// キーバインド設定ファイルを開くコマンド
-#[tauri::command]
-fn open_config_file() -> Result<(), String> {
-    let path = get_keybindings_config_path().ok_or("設定ファイルのパスが見つかりません")?;
-    open_file_with_default_app(&path)
+/// トレイのツールチップを前回のアクティブアプリ情報で更新
+fn update_tray_tooltip(app: &AppHandle) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let language = load_settings().language.resolve();
+        let tooltip = format_tray_tooltip(get_last_active_app().as_ref(), language);
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
 }
 
-// settings.jsonファイルを開くコマンド
+// 現在の最前面ウィンドウの情報を取得するコマンド（不具合報告用の診断情報、自分自身は除外）
 #[tauri::command]
-fn open_settings_file() -> Result<(), String> {
-    let path = get_settings_path().ok_or("設定ファイルのパスが見つかりません")?;
-
-    // ファイルが存在しない場合は作成
-    if !path.exists() {
-        let settings = AppSettings::default();
-        save_settings(&settings)?;
-    }
+#[allow(clippy::missing_const_for_fn)] // Windows以外ではダミー実装のためconst化できてしまう
+fn debug_active_window() -> Option<ActiveWindowInfo> {
+    active_window::peek_active_window_info()
+}
 
-    open_file_with_default_app(&path)
+// 前回のアクティブアプリ情報を取得する
+fn get_last_active_app() -> Option<ActiveWindowInfo> {
+    LAST_ACTIVE_APP.lock().ok()?.clone()
 }
 
-// テーマ設定を取得
-#[tauri::command]
-fn get_theme_setting() -> String {
-    let settings = load_settings();
-    match settings.theme {
-        ThemeSetting::System => "system".to_string(),
-        ThemeSetting::Light => "light".to_string(),
-        ThemeSetting::Dark => "dark".to_string(),
+// ウィンドウの表示/非表示を切り替え
+fn toggle_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("search") {
+        if window.is_visible().unwrap_or(false) {
+            WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+            let _ = window.hide();
+        } else {
+            show_main_window(&window);
+        }
     }
 }
 
-// テーマ設定を保存
-#[tauri::command]
-fn set_theme_setting(theme: String) -> Result<(), String> {
-    let mut settings = load_settings();
-    settings.theme = match theme.as_str() {
-        "light" => ThemeSetting::Light,
-        "dark" => ThemeSetting::Dark,
-        _ => ThemeSetting::System,
+/// メインウィンドウを表示してフォーカスし、フロントエンドに通知
+fn show_main_window(window: &WebviewWindow) {
+    // 保存しておいた前回のアクティブアプリを使用
+    let active_window = get_last_active_app();
+    let payload = WindowShownPayload {
+        matched_apps: active_window.as_ref().map_or_else(Vec::new, |info| {
+            match_apps_with_icons(info, &load_keybindings_config())
+        }),
+        active_window,
+        escape_to_hide: load_settings().escape_to_hide,
     };
-    save_settings(&settings)
+    store_last_shown_payload(&payload);
+
+    WINDOW_VISIBLE.store(true, Ordering::SeqCst);
+    position_main_window(window);
+    let _ = window.show();
+    let _ = window.set_focus();
+    // フロントエンドに通知（アクティブアプリ名を含む）
+    let _ = window.emit("window-shown", payload);
 }
 
-// システムテーマを取得（ウィンドウから）
-#[tauri::command]
-fn get_system_theme(window: WebviewWindow) -> String {
-    if matches!(window.theme(), Ok(tauri::Theme::Dark)) {
-        "dark".to_string()
+/// カーソルがあるモニターの作業領域の中央にウィンドウを配置
+/// カーソル位置が取得できない場合はプライマリモニターを使用
+fn center_on_cursor_monitor(window: &WebviewWindow) {
+    let monitor = window
+        .cursor_position()
+        .ok()
+        .and_then(|cursor| window.monitor_from_point(cursor.x, cursor.y).ok().flatten())
+        .or_else(|| window.primary_monitor().ok().flatten());
+    let (Some(monitor), Ok(size)) = (monitor, window.outer_size()) else {
+        let _ = window.center();
+        return;
+    };
+
+    let area = monitor.work_area();
+    let offset = |area_len: u32, window_len: u32| (i64::from(area_len) - i64::from(window_len)) / 2;
+    let x = i64::from(area.position.x) + offset(area.size.width, size.width);
+    let y = i64::from(area.position.y) + offset(area.size.height, size.height);
+    if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
+        let _ = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }));
     } else {
-        "light".to_string()
+        let _ = window.center();
     }
 }
 
-// オーバーレイ表示用のペイロード
-#[derive(Clone, Serialize)]
-struct OverlayPayload {
-    app_name: String,
-    action_name: String,
-    shortcut_key: String,
-    duration: u32,
-    theme: String,
+/// 保存した位置とサイズが接続中のいずれかのモニター上にあるか
+fn is_bounds_on_monitor(window: &WebviewWindow, bounds: &WindowBounds) -> bool {
+    // ウィンドウの中心がモニター内にあれば表示可能とみなす
+    let center_x = i64::from(bounds.x) + i64::from(bounds.width) / 2;
+    let center_y = i64::from(bounds.y) + i64::from(bounds.height) / 2;
+    window.available_monitors().is_ok_and(|monitors| {
+        monitors.iter().any(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            let left = i64::from(position.x);
+            let top = i64::from(position.y);
+            (left..left + i64::from(size.width)).contains(&center_x)
+                && (top..top + i64::from(size.height)).contains(&center_y)
+        })
+    })
 }
 
-/// オーバーレイウィンドウの幅を計算
-#[allow(clippy::cast_precision_loss)] // ステップ数・区切り文字数は小さな整数なので精度損失なし
-fn calculate_overlay_width(shortcut_key: &str) -> f64 {
-    const BASE_WIDTH: f64 = 150.0;
-    const MODIFIER_WIDTH: f64 = 50.0;
-    const SEPARATOR_WIDTH: f64 = 20.0;
-    const SEQUENCE_SEPARATOR_WIDTH: f64 = 30.0;
-    const DEFAULT_KEY_WIDTH: f64 = 30.0;
-    const MIN_WIDTH: f64 = 200.0;
-    const MAX_WIDTH: f64 = 600.0;
-
-    let mut width = BASE_WIDTH;
-
-    // 順次入力キーの場合、各ステップを分割して計算
-    let steps: Vec<&str> = shortcut_key.split(" → ").collect();
-    let is_sequence = steps.len() > 1;
-
-    for step in &steps {
-        let step_lower = step.to_lowercase();
-
-        // 修飾キーの幅を加算（各ステップごとにカウント）
-        if step_lower.contains("ctrl") || step_lower.contains("control") {
-            width += MODIFIER_WIDTH;
-        }
-        if step_lower.contains("shift") {
-            width += MODIFIER_WIDTH;
-        }
-        if step_lower.contains("alt") || step_lower.contains("option") {
-            width += MODIFIER_WIDTH;
-        }
-        if step_lower.contains("win")
-            || step_lower.contains("command")
-            || step_lower.contains("cmd")
-            || step.contains('⌘')
-        {
-            width += MODIFIER_WIDTH;
+/// メインウィンドウを配置（保存した位置があれば復元、なければカーソルのモニター中央）
+fn position_main_window(window: &WebviewWindow) {
+    let settings = load_settings();
+    if !settings.always_center {
+        if let Some(bounds) = settings.window_bounds {
+            if is_bounds_on_monitor(window, &bounds) {
+                let _ = window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
+                    width: bounds.width,
+                    height: bounds.height,
+                }));
+                let _ = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+                    x: bounds.x,
+                    y: bounds.y,
+                }));
+                remember_applied_bounds(window);
+                return;
+            }
         }
-
-        // 同時押し区切り文字の幅を加算
-        let separator_count = step.matches('+').count();
-        width += (separator_count as f64) * SEPARATOR_WIDTH;
-
-        // キー自体の幅を加算
-        width += DEFAULT_KEY_WIDTH;
     }
+    center_on_cursor_monitor(window);
+    remember_applied_bounds(window);
+}
 
-    // 順次入力の区切り文字（→）の幅を加算
-    if is_sequence {
-        width += ((steps.len() - 1) as f64) * SEQUENCE_SEPARATOR_WIDTH;
+/// アプリ自身が配置したウィンドウの位置とサイズを記録（移動イベントでの保存対象から除外するため）
+fn remember_applied_bounds(window: &WebviewWindow) {
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
+    };
+    if let Ok(mut applied) = APPLIED_WINDOW_BOUNDS.lock() {
+        *applied = Some(WindowBounds {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        });
     }
+}
 
-    // 最小・最大幅でクランプ
-    width.clamp(MIN_WIDTH, MAX_WIDTH)
+/// アプリ自身が最後に配置した位置とサイズと一致するか
+fn is_applied_bounds(bounds: WindowBounds) -> bool {
+    APPLIED_WINDOW_BOUNDS
+        .lock()
+        .is_ok_and(|applied| *applied == Some(bounds))
 }
 
-/// Windowsでフォーカスを奪わずにウィンドウを表示
-#[cfg(target_os = "windows")]
-#[allow(unsafe_code)]
-fn show_window_no_focus(window: &WebviewWindow) {
-    use windows::Win32::UI::WindowsAndMessaging::{
-        SetWindowPos, ShowWindow, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
-        SW_SHOWNOACTIVATE,
+/// メインウィンドウの位置とサイズの保存を予約（500ms以内の変更はまとめて保存）
+fn schedule_window_bounds_save(window: &tauri::Window) {
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else {
+        return;
     };
+    // 最小化時などサイズが0の場合は保存しない
+    if size.width == 0 || size.height == 0 {
+        return;
+    }
+    if let Ok(mut pending) = PENDING_WINDOW_BOUNDS.lock() {
+        *pending = Some(WindowBounds {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        });
+    }
+    if WINDOW_BOUNDS_SAVE_SCHEDULED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(|| {
+        thread::sleep(Duration::from_millis(500));
+        WINDOW_BOUNDS_SAVE_SCHEDULED.store(false, Ordering::SeqCst);
+        let Some(bounds) = PENDING_WINDOW_BOUNDS
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.take())
+        else {
+            return;
+        };
+        // アプリ自身が配置した位置（中央表示など）は保存しない
+        if is_applied_bounds(bounds) {
+            return;
+        }
+        let mut settings = load_settings();
+        if settings.always_center || settings.window_bounds == Some(bounds) {
+            return;
+        }
+        settings.window_bounds = Some(bounds);
+        let _ = save_settings(&settings);
+    });
+}
 
-    // HWNDを取得
-    if let Ok(hwnd) = window.hwnd() {
-        let hwnd = windows::Win32::Foundation::HWND(hwnd.0.cast());
+// 送信したwindow-shownペイロードを保存する
+fn store_last_shown_payload(payload: &WindowShownPayload) {
+    if let Ok(mut last_payload) = LAST_SHOWN_PAYLOAD.lock() {
+        *last_payload = Some(payload.clone());
+    }
+}
 
-        // SAFETY: 以下のWindows API呼び出しは安全です：
-        // - ShowWindow: 有効なHWND（Tauriから取得）に対してウィンドウの表示状態を変更
-        //   SW_SHOWNOACTIVATEはフォーカスを移動せずにウィンドウを表示
-        // - SetWindowPos: HWND_TOPMOSTでZオーダーを最前面に設定
-        //   SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZEにより位置/サイズ変更なし
-        unsafe {
-            // SW_SHOWNOACTIVATEでフォーカスを奪わずに表示
-            let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+// 最後に送信したwindow-shownペイロードを取得するコマンド
+#[tauri::command]
+fn get_last_shown_payload() -> Option<WindowShownPayload> {
+    LAST_SHOWN_PAYLOAD.lock().ok()?.clone()
+}
 
-            // 常に最前面に配置（フォーカスは奪わない）
-            let _ = SetWindowPos(
-                hwnd,
-                HWND_TOPMOST,
-                0,
-                0,
-                0,
-                0,
-                SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZE,
-            );
-        }
+// ウィンドウを非表示
+fn hide_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("search") {
+        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+        let _ = window.hide();
+        let _ = window.emit("window-hidden", ());
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-fn show_window_no_focus(window: &WebviewWindow) {
-    let _ = window.show();
+/// 監視ループの1回分の処理を行い、次の確認までの待ち時間を返す
+/// 監視が無効の間は何もせず、間隔を空けて再確認する
+/// 有効な場合はウィンドウが非表示の時だけアクティブアプリを更新し、200msごとに監視する
+fn run_monitor_iteration(update: impl FnOnce()) -> Duration {
+    if !MONITOR_ENABLED.load(Ordering::SeqCst) {
+        return Duration::from_secs(1);
+    }
+    if !WINDOW_VISIBLE.load(Ordering::SeqCst) {
+        update();
+    }
+    Duration::from_millis(200)
 }
 
-// オーバーレイウィンドウを表示
-#[tauri::command]
-#[allow(clippy::unnecessary_wraps)] // フロントエンドとの互換性のため Result を返す
-fn show_overlay(
-    app: AppHandle,
-    app_name: String,
-    action_name: String,
-    shortcut_key: String,
-) -> Result<(), String> {
-    let settings = load_settings();
-    let duration = settings.overlay_duration;
-    let theme = match settings.theme {
-        ThemeSetting::Light => "light".to_string(),
-        ThemeSetting::Dark => "dark".to_string(),
-        ThemeSetting::System => "system".to_string(),
+// バックグラウンドでアクティブウィンドウを監視するスレッドを開始
+fn start_active_window_monitor(app: AppHandle) {
+    MONITOR_STOP.store(false, Ordering::SeqCst);
+    let handle = thread::spawn(move || {
+        while !MONITOR_STOP.load(Ordering::SeqCst) {
+            // 変化があればトレイのツールチップと検出中のアプリのメニューも更新
+            // （Linuxのトレイはポインタが乗ったことを通知しないため、ここで作り直す）
+            let interval = run_monitor_iteration(|| {
+                if update_last_active_app() {
+                    update_tray_tooltip(&app);
+                    if let Err(e) = refresh_tray_matched_apps(&app) {
+                        eprintln!("Warning: {e}");
+                    }
+                }
+            });
+            thread::sleep(interval);
+        }
+    });
+    if let Ok(mut monitor_thread) = MONITOR_THREAD.lock() {
+        *monitor_thread = Some(handle);
+    }
+}
+
+/// アクティブウィンドウの監視スレッドを停止して終了を待つ
+/// スレッドがメインスレッドの処理待ちで止まっている場合に備え、待機は最大1秒まで
+fn stop_active_window_monitor() {
+    MONITOR_STOP.store(true, Ordering::SeqCst);
+    let Some(handle) = MONITOR_THREAD
+        .lock()
+        .ok()
+        .and_then(|mut thread| thread.take())
+    else {
+        return;
     };
+    for _ in 0..20 {
+        if handle.is_finished() {
+            let _ = handle.join();
+            return;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+}
 
-    // メインウィンドウを非表示
-    if let Some(main_window) = app.get_webview_window("search") {
-        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
-        let _ = main_window.hide();
+// ウィンドウを非表示にするコマンド
+#[tauri::command]
+fn hide_main_window(app: AppHandle) {
+    hide_window(&app);
+}
+
+// プラットフォームを取得するコマンド
+#[tauri::command]
+fn get_platform() -> String {
+    if cfg!(target_os = "macos") {
+        "darwin".to_string()
+    } else if cfg!(target_os = "windows") {
+        "win32".to_string()
+    } else {
+        "linux".to_string()
     }
+}
 
-    // オーバーレイウィンドウを表示（フォーカスは設定しない）
-    if let Some(overlay_window) = app.get_webview_window("keyguide") {
-        // ウィンドウ幅を計算して設定
-        let width = calculate_overlay_width(&shortcut_key);
-        let _ = overlay_window.set_size(tauri::Size::Logical(tauri::LogicalSize {
-            width,
-            height: 150.0,
-        }));
+/// アクティブウィンドウにマッチするアプリを検索
+/// プロセス名またはウィンドウタイトルで完全一致（大文字小文字無視）
+/// `fallback`なアプリは、他のアプリが1つもマッチしない場合のみ含める
+fn match_apps(info: &ActiveWindowInfo, apps: &[AppConfig]) -> Vec<NormalizedApp> {
+    matched_app_configs(info, apps)
+        .1
+        .into_iter()
+        .map(|app| normalize_app(app, None))
+        .collect()
+}
 
-        // 保存された位置があればその位置に、なければ中央に表示
-        if let (Some(x), Some(y)) = (settings.overlay_position.x, settings.overlay_position.y) {
-            let _ = overlay_window
-                .set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }));
-        } else {
-            let _ = overlay_window.center();
-        }
+/// マッチしたアプリ情報に、キャッシュ済みの自動取得アイコンを付けて返す（ウィンドウの表示用）
+/// `info` は監視しているアクティブウィンドウの情報とし、WebViewから受け取った実行ファイルのパスは使わない
+fn match_apps_with_icons(info: &ActiveWindowInfo, apps: &[AppConfig]) -> Vec<NormalizedApp> {
+    let (info, matched) = matched_app_configs(info, apps);
+    matched
+        .into_iter()
+        .map(|app| {
+            let icon_url = info
+                .executable
+                .as_deref()
+                .filter(|_| app.auto_icon)
+                .and_then(cached_auto_icon_url);
+            normalize_app(app, icon_url)
+        })
+        .collect()
+}
 
-        // フォーカスを奪わずに表示
-        show_window_no_focus(&overlay_window);
+/// マッチングに使ったウィンドウと、マッチしたアプリ（優先度の高い順、同じ優先度は設定順）
+fn matched_app_configs<'a, 'b>(
+    info: &'a ActiveWindowInfo,
+    apps: &'b [AppConfig],
+) -> (&'a ActiveWindowInfo, Vec<&'b AppConfig>) {
+    let (info, has_match) = match_context(info, apps);
+    let mut matched: Vec<&AppConfig> = apps
+        .iter()
+        .filter(|app| app.is_available() && is_app_selected(info, app, has_match))
+        .collect();
+    matched.sort_by_key(|app| std::cmp::Reverse(app.match_priority));
+    (info, matched)
+}
 
-        // 元のアプリにフォーカスを戻す
-        active_window::restore_focus_to_last_window();
+/// アプリ設定をフロントエンドに渡すアプリ情報に変換
+fn normalize_app(app: &AppConfig, icon_url: Option<String>) -> NormalizedApp {
+    NormalizedApp {
+        name: app.get_name(),
+        icon: app.get_icon(),
+        icon_url,
+        url: app.url.clone(),
+    }
+}
 
-        // オーバーレイにデータを送信
-        let _ = overlay_window.emit(
-            "overlay-show",
-            OverlayPayload {
-                app_name,
-                action_name,
-                shortcut_key,
-                duration,
-                theme,
-            },
-        );
+/// マッチングに使うウィンドウと、`fallback`でないアプリがマッチしたかを求める
+/// 現在のプラットフォームで有効なアプリのみを対象とする（他OS・無効なアプリはfallbackを抑制しない）
+fn match_context<'a>(
+    info: &'a ActiveWindowInfo,
+    apps: &[AppConfig],
+) -> (&'a ActiveWindowInfo, bool) {
+    let available = || apps.iter().filter(|app| app.is_available());
+    let info = match_target(info, available());
+    (info, has_non_fallback_match(info, available()))
+}
 
-        // Rust側でタイマーを管理（フォーカスがなくてもタイマーが動作するように）
-        thread::spawn(move || {
-            thread::sleep(Duration::from_secs(u64::from(duration)));
-            if let Some(overlay) = app.get_webview_window("keyguide") {
-                // Windows API で直接非表示にする（Tauriのhide()が効かない場合の対策）
-                #[cfg(target_os = "windows")]
-                {
-                    if let Ok(hwnd) = overlay.hwnd() {
-                        use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
-                        // SAFETY: ShowWindowは有効なHWND（Tauriから取得）に対して
-                        // SW_HIDEでウィンドウを非表示にする。失敗しても安全。
-                        #[allow(unsafe_code)]
-                        unsafe {
-                            let hwnd = windows::Win32::Foundation::HWND(hwnd.0.cast());
-                            let _ = ShowWindow(hwnd, SW_HIDE);
-                        }
-                    }
-                }
-                #[cfg(not(target_os = "windows"))]
-                {
-                    let _ = overlay.hide();
-                }
-            }
-        });
+/// マッチングに使うウィンドウを決める
+/// 最前面のウィンドウに`fallback`でないアプリが1つもマッチせず、所有者ウィンドウにはマッチする場合は所有者を使う
+fn match_target<'a, 'b>(
+    info: &'a ActiveWindowInfo,
+    apps: impl Iterator<Item = &'b AppConfig> + Clone,
+) -> &'a ActiveWindowInfo {
+    match info.owner.as_deref() {
+        Some(owner)
+            if !has_non_fallback_match(info, apps.clone())
+                && has_non_fallback_match(owner, apps) =>
+        {
+            owner
+        }
+        _ => info,
     }
+}
 
-    Ok(())
+/// `fallback`でないアプリが1つでもマッチするか
+fn has_non_fallback_match<'a>(
+    info: &ActiveWindowInfo,
+    mut apps: impl Iterator<Item = &'a AppConfig>,
+) -> bool {
+    apps.any(|app| !app.fallback && is_app_matched(info, app))
 }
 
-// オーバーレイウィンドウを非表示
-#[tauri::command]
-fn hide_overlay(app: AppHandle) {
-    if let Some(overlay_window) = app.get_webview_window("keyguide") {
-        let _ = overlay_window.hide();
+/// アプリを表示対象とするか（`fallback`なアプリは他にマッチがない場合のみ）
+fn is_app_selected(info: &ActiveWindowInfo, app: &AppConfig, has_match: bool) -> bool {
+    if app.fallback {
+        !has_match
+    } else {
+        is_app_matched(info, app)
     }
 }
 
-// キーバインド設定を生データで取得（設定画面用）
-#[tauri::command]
-fn get_keybindings_raw() -> Vec<AppConfig> {
-    load_keybindings_config()
+/// アプリのバインドがアクティブウィンドウにマッチするか
+fn is_app_matched(info: &ActiveWindowInfo, app: &AppConfig) -> bool {
+    app.get_binds().iter().any(|bind| {
+        let bind = bind.to_lowercase();
+        // プロセス名で完全一致
+        let process_matched = info
+            .process
+            .as_ref()
+            .is_some_and(|process| process.to_lowercase() == bind);
+        // ウィンドウタイトルで完全一致
+        let window_matched = info
+            .window
+            .as_ref()
+            .is_some_and(|window| window.to_lowercase() == bind);
+        process_matched || window_matched
+    })
 }
 
-// キーバインド設定を保存（設定画面用）
+// マッチしたアプリ情報を取得するコマンド
+// 渡された実行ファイルのパスは信頼せず、アイコンは付けない（アイコンは window-shown で渡す）
 #[tauri::command]
-fn save_keybindings(config: Vec<AppConfig>) -> Result<(), String> {
-    save_keybindings_config(&config)
+fn get_matched_apps(info: Option<ActiveWindowInfo>) -> Vec<NormalizedApp> {
+    let config = load_keybindings_config();
+    info.as_ref()
+        .map_or_else(Vec::new, |window_info| match_apps(window_info, &config))
+}
+
+/// マッチしたアプリを最近使ったアプリとして記録（アクティブウィンドウが変わった時のみ呼び出す）
+fn record_recent_apps(apps: &[NormalizedApp]) {
+    if let Ok(mut recent) = RECENT_APPS.lock() {
+        push_recent_apps(&mut recent, apps, SystemTime::now());
+    }
 }
 
-// キーバインド設定をデフォルトに戻す
+/// 最近使ったアプリの一覧にマッチした時刻を記録（同じアプリは時刻を更新）
+fn push_recent_apps(
+    recent: &mut Vec<(NormalizedApp, SystemTime)>,
+    apps: &[NormalizedApp],
+    now: SystemTime,
+) {
+    for app in apps {
+        recent.retain(|(existing, _)| existing.name != app.name);
+        recent.push((app.clone(), now));
+    }
+}
+
+/// 最近使ったアプリを新しい順に最大 `limit` 件取得
+fn newest_recent_apps(recent: &[(NormalizedApp, SystemTime)], limit: usize) -> Vec<NormalizedApp> {
+    let mut apps: Vec<_> = recent.iter().collect();
+    apps.sort_by_key(|(_, matched_at)| std::cmp::Reverse(*matched_at));
+    apps.into_iter()
+        .take(limit)
+        .map(|(app, _)| app.clone())
+        .collect()
+}
+
+// 最近マッチしたアプリを新しい順に取得するコマンド
 #[tauri::command]
-fn reset_keybindings() -> Result<Vec<AppConfig>, String> {
-    let defaults = get_default_keybindings();
-    save_keybindings_config(&defaults)?;
-    Ok(defaults)
+fn get_recent_apps(limit: usize) -> Vec<NormalizedApp> {
+    RECENT_APPS
+        .lock()
+        .map(|recent| newest_recent_apps(&recent, limit))
+        .unwrap_or_default()
 }
 
-// キーバインド設定ウィンドウを開く
+// 任意のプロセス名・ウィンドウタイトルでマッチするアプリを確認するコマンド（設定のデバッグ用）
+// 監視中のアクティブウィンドウ情報は参照・更新しない
 #[tauri::command]
-fn open_keybindings_window(app: AppHandle) -> Result<(), String> {
-    app.get_webview_window("keybindings").map_or_else(
-        || Err("キーバインド設定ウィンドウが見つかりません".to_string()),
-        |window| {
-            // ウィンドウを中央に配置して表示
-            let _ = window.center();
-            let _ = window.show();
-            let _ = window.set_focus();
-            Ok(())
+fn simulate_match(process: Option<String>, window: Option<String>) -> Vec<NormalizedApp> {
+    match_apps(
+        &ActiveWindowInfo {
+            process,
+            window,
+            executable: None,
+            owner: None,
         },
+        &load_keybindings_config(),
     )
 }
 
-// キーバインド設定ウィンドウを閉じる（非表示にする）
+/// アクティブウィンドウ向けのショートカット一覧を作成
+/// マッチしたアプリのみを対象とし、`title_contains`付きのキーバインドは
+/// ウィンドウタイトルが指定文字列を含む場合のみ含める（大文字小文字無視）
+/// `global`なアプリはマッチしなくても常に先頭に含め、`fallback`なアプリは他にマッチがない場合のみ含める
+fn shortcuts_for_window(
+    info: &ActiveWindowInfo,
+    apps: &[AppConfig],
+    key_display_style: KeyDisplayStyle,
+) -> Vec<NormalizedShortcut> {
+    normalize_shortcuts(apps_for_window(info, apps), key_display_style)
+}
+
+/// アクティブウィンドウに表示するアプリ（globalなアプリが先頭、キーバインドは `title_contains` で絞り込み済み）
+fn apps_for_window(info: &ActiveWindowInfo, apps: &[AppConfig]) -> Vec<AppConfig> {
+    let (info, has_match) = match_context(info, apps);
+    let window_title = info.window.as_deref().unwrap_or_default().to_lowercase();
+    let (global_apps, matched_apps): (Vec<_>, Vec<_>) = apps
+        .iter()
+        .filter(|app| app.is_available() && (app.global || is_app_selected(info, app, has_match)))
+        .cloned()
+        .partition(|app| app.global);
+    global_apps
+        .into_iter()
+        .chain(matched_apps)
+        .map(|mut app| {
+            app.keybindings.retain(|kb| {
+                kb.title_contains
+                    .as_ref()
+                    .is_none_or(|pattern| window_title.contains(&pattern.to_lowercase()))
+            });
+            app
+        })
+        .collect()
+}
+
+// アクティブウィンドウにマッチしたアプリのショートカット一覧を取得するコマンド
 #[tauri::command]
-fn close_keybindings_window(app: AppHandle) {
-    if let Some(window) = app.get_webview_window("keybindings") {
-        let _ = window.hide();
+fn get_shortcuts_for_active(info: Option<ActiveWindowInfo>) -> Vec<NormalizedShortcut> {
+    let config = load_keybindings_config();
+    let key_display_style = load_settings().key_display_style;
+    let Some(window_info) = info else {
+        // アクティブウィンドウが不明でもglobalなアプリは表示
+        return normalize_shortcuts(
+            config
+                .iter()
+                .filter(|app| app.global && app.is_available())
+                .cloned(),
+            key_display_style,
+        );
+    };
+    shortcuts_for_window(&window_info, &config, key_display_style)
+}
+
+/// アプリ設定のキーバインドを実行中のOSのキーでフロントエンド用に正規化
+fn normalize_shortcuts(
+    apps: impl IntoIterator<Item = AppConfig>,
+    key_display_style: KeyDisplayStyle,
+) -> Vec<NormalizedShortcut> {
+    normalize_shortcuts_with(apps, &OsType::current(), false, key_display_style)
+}
+
+/// アプリ設定のキーバインドを指定したOSのキーでフロントエンド用に正規化
+/// `include_unbound` が true の場合、未割り当て（"-" やOS別の指定なし）のものも空のキーと `unbound` 付きで含める
+/// キーの表示形式は呼び出し元で設定から読み込んで渡す（ここではファイルを読まない）
+fn normalize_shortcuts_with(
+    apps: impl IntoIterator<Item = AppConfig>,
+    os: &OsType,
+    include_unbound: bool,
+    key_display_style: KeyDisplayStyle,
+) -> Vec<NormalizedShortcut> {
+    apps.into_iter()
+        .flat_map(|app| {
+            let app_name = app.get_name();
+            let app_icon = app.get_icon();
+            app.keybindings.into_iter().filter_map(move |kb| {
+                let kb_key = kb.get_key(os);
+                let unbound = kb_key.is_none();
+                // キーが未割り当ての場合（含める指定がなければ）、またはアクションが空の場合は対象外
+                if (unbound && !include_unbound) || kb.action.trim().is_empty() {
+                    return None;
+                }
+                let key = kb_key.map_or_else(String::new, |kb_key| {
+                    // 表示用に正規化（スペースあり形式に統一）
+                    let key = normalize_key_for_display(&kb_key);
+                    // 順次入力キーの区切り文字を変換: "->" → "→"
+                    let key = key.replace(" -> ", " → ");
+                    apply_key_display_style_for(&key, key_display_style, os)
+                });
+                Some(NormalizedShortcut {
+                    app: app_name.clone(),
+                    icon: kb.icon.unwrap_or_else(|| app_icon.clone()),
+                    action: kb.action,
+                    key,
+                    tags: kb.tags,
+                    description: kb.description,
+                    category: kb.category,
+                    unbound,
+                })
+            })
+        })
+        .collect()
+}
+
+/// 表示形式に合わせて修飾キーを変換（symbols の場合、macOSでのみ記号に置き換え）
+/// 区切り（" + " と " → "）は維持するため、オーバーレイのキー分割はそのまま使える
+fn apply_key_display_style(key: &str, style: KeyDisplayStyle) -> String {
+    apply_key_display_style_for(key, style, &OsType::current())
+}
+
+/// 指定したOSの表示形式に合わせて修飾キーを変換
+fn apply_key_display_style_for(key: &str, style: KeyDisplayStyle, os: &OsType) -> String {
+    if style == KeyDisplayStyle::Verbose || *os != OsType::MacOS {
+        return key.to_string();
     }
+    key.split(" → ")
+        .map(|step| {
+            step.split(" + ")
+                .map(|part| match part.to_lowercase().as_str() {
+                    "command" | "cmd" => "⌘",
+                    "shift" => "⇧",
+                    "option" | "alt" => "⌥",
+                    "control" | "ctrl" => "⌃",
+                    _ => part,
+                })
+                .collect::<Vec<_>>()
+                .join(" + ")
+        })
+        .collect::<Vec<_>>()
+        .join(" → ")
 }
 
-// アプリのバージョンを取得
-#[tauri::command]
-fn get_app_version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
+/// アクションが空（空白のみを含む）のキーバインドを「アプリ名: キー」形式で列挙
+fn find_empty_actions(apps: &[AppConfig]) -> Vec<String> {
+    apps.iter()
+        .flat_map(|app| {
+            let app_name = app.get_name();
+            app.keybindings
+                .iter()
+                .filter(|kb| kb.action.trim().is_empty())
+                .map(move |kb| {
+                    let key = kb.get_key(&OsType::current());
+                    format!("{app_name}: {}", key.as_deref().unwrap_or("-"))
+                })
+        })
+        .collect()
 }
 
-// バージョン情報ウィンドウを開く
+// アクションが空のキーバインドを取得するコマンド（設定画面での警告表示用）
 #[tauri::command]
-fn open_about_window(app: AppHandle) -> Result<(), String> {
-    app.get_webview_window("about").map_or_else(
-        || Err("バージョン情報ウィンドウが見つかりません".to_string()),
-        |window| {
-            let _ = window.center();
-            let _ = window.show();
-            let _ = window.set_focus();
-            Ok(())
-        },
-    )
+fn get_empty_actions() -> Vec<String> {
+    find_empty_actions(&load_keybindings_config())
 }
 
-// バージョン情報ウィンドウを閉じる（非表示にする）
-#[tauri::command]
-fn close_about_window(app: AppHandle) {
-    if let Some(window) = app.get_webview_window("about") {
-        let _ = window.hide();
+/// ポリシーに違反しているショートカットを「アプリ名: アクション」形式で列挙
+/// `require_tags` が有効な場合、タグのないショートカットを違反とする
+fn find_policy_violations(shortcuts: &[NormalizedShortcut], require_tags: bool) -> Vec<String> {
+    if !require_tags {
+        return Vec::new();
     }
+    shortcuts
+        .iter()
+        .filter(|shortcut| shortcut.tags.is_empty())
+        .map(|shortcut| format!("{}: {}", shortcut.app, shortcut.action))
+        .collect()
 }
 
-// オーバーレイの位置を保存
+// 設定のポリシーに違反しているショートカットを取得するコマンド
 #[tauri::command]
-fn save_overlay_position(x: i32, y: i32) -> Result<(), String> {
-    let mut settings = load_settings();
-    settings.overlay_position = OverlayPosition {
-        x: Some(x),
-        y: Some(y),
-    };
-    save_settings(&settings)
+fn get_policy_violations() -> Vec<String> {
+    find_policy_violations(&get_shortcuts(), load_settings().require_tags)
 }
 
-/// ホットキー文字列をパースしてShortcut構造体に変換
-#[allow(clippy::cognitive_complexity)] // キーコードマッピングのため複雑になるが明確な構造
-fn parse_hotkey(hotkey: &str) -> Option<Shortcut> {
-    let normalized = normalize_hotkey_for_tauri(hotkey);
-    let parts: Vec<&str> = normalized.split('+').collect();
+// ショートカット一覧を取得するコマンド
+#[tauri::command]
+fn get_shortcuts() -> Vec<NormalizedShortcut> {
+    let config = load_keybindings_config();
 
-    if parts.is_empty() {
-        return None;
+    // 現在のプラットフォームで有効なアプリのみ
+    normalize_shortcuts(
+        config.iter().filter(|app| app.is_available()).cloned(),
+        load_settings().key_display_style,
+    )
+}
+
+/// ショートカットを並び替える（大文字小文字無視、同じ値は元の順序を維持）
+fn sort_shortcuts(shortcuts: &mut [NormalizedShortcut], by: ShortcutSort) {
+    match by {
+        ShortcutSort::Config => {}
+        ShortcutSort::App => shortcuts.sort_by_cached_key(|s| s.app.to_lowercase()),
+        ShortcutSort::Action => shortcuts.sort_by_cached_key(|s| s.action.to_lowercase()),
+        ShortcutSort::Key => shortcuts.sort_by_cached_key(|s| s.key.to_lowercase()),
     }
+}
 
-    let mut modifiers = Modifiers::empty();
-    let mut key_code: Option<Code> = None;
+// 並び替えたショートカット一覧を取得するコマンド（by: "app" / "action" / "key"、不明な値は設定順）
+#[tauri::command]
+fn get_shortcuts_sorted(by: String) -> Vec<NormalizedShortcut> {
+    let mut shortcuts = get_shortcuts();
+    sort_shortcuts(
+        &mut shortcuts,
+        ShortcutSort::from_name(&by).unwrap_or_default(),
+    );
+    shortcuts
+}
 
-    for part in parts {
-        let part_lower = part.to_lowercase();
-        match part_lower.as_str() {
-            "ctrl" | "control" => modifiers |= Modifiers::CONTROL,
-            "shift" => modifiers |= Modifiers::SHIFT,
-            "alt" | "option" => modifiers |= Modifiers::ALT,
-            "cmd" | "command" | "super" | "win" | "meta" => modifiers |= Modifiers::META,
-            _ => {
-                // キーコードを解析
-                key_code = match part_lower.as_str() {
-                    "a" => Some(Code::KeyA),
-                    "b" => Some(Code::KeyB),
-                    "c" => Some(Code::KeyC),
-                    "d" => Some(Code::KeyD),
-                    "e" => Some(Code::KeyE),
-                    "f" => Some(Code::KeyF),
-                    "g" => Some(Code::KeyG),
-                    "h" => Some(Code::KeyH),
-                    "i" => Some(Code::KeyI),
-                    "j" => Some(Code::KeyJ),
-                    "k" => Some(Code::KeyK),
-                    "l" => Some(Code::KeyL),
-                    "m" => Some(Code::KeyM),
-                    "n" => Some(Code::KeyN),
-                    "o" => Some(Code::KeyO),
-                    "p" => Some(Code::KeyP),
-                    "q" => Some(Code::KeyQ),
-                    "r" => Some(Code::KeyR),
-                    "s" => Some(Code::KeyS),
-                    "t" => Some(Code::KeyT),
-                    "u" => Some(Code::KeyU),
-                    "v" => Some(Code::KeyV),
-                    "w" => Some(Code::KeyW),
-                    "x" => Some(Code::KeyX),
-                    "y" => Some(Code::KeyY),
-                    "z" => Some(Code::KeyZ),
-                    "0" => Some(Code::Digit0),
-                    "1" => Some(Code::Digit1),
-                    "2" => Some(Code::Digit2),
-                    "3" => Some(Code::Digit3),
-                    "4" => Some(Code::Digit4),
-                    "5" => Some(Code::Digit5),
-                    "6" => Some(Code::Digit6),
-                    "7" => Some(Code::Digit7),
-                    "8" => Some(Code::Digit8),
-                    "9" => Some(Code::Digit9),
-                    "space" => Some(Code::Space),
-                    "enter" | "return" => Some(Code::Enter),
-                    "escape" | "esc" => Some(Code::Escape),
-                    "tab" => Some(Code::Tab),
-                    "backspace" => Some(Code::Backspace),
-                    "delete" => Some(Code::Delete),
-                    "up" => Some(Code::ArrowUp),
-                    "down" => Some(Code::ArrowDown),
-                    "left" => Some(Code::ArrowLeft),
-                    "right" => Some(Code::ArrowRight),
-                    "f1" => Some(Code::F1),
-                    "f2" => Some(Code::F2),
-                    "f3" => Some(Code::F3),
-                    "f4" => Some(Code::F4),
-                    "f5" => Some(Code::F5),
-                    "f6" => Some(Code::F6),
-                    "f7" => Some(Code::F7),
-                    "f8" => Some(Code::F8),
-                    "f9" => Some(Code::F9),
-                    "f10" => Some(Code::F10),
-                    "f11" => Some(Code::F11),
-                    "f12" => Some(Code::F12),
-                    _ => None,
-                };
-            }
+// ショートカット一覧の初期の並び順を取得するコマンド
+#[tauri::command]
+fn get_default_shortcut_sort() -> ShortcutSort {
+    load_settings().default_shortcut_sort
+}
+
+// キーが割り当てられていない（"-"）ものも含めてショートカット一覧を取得するコマンド
+// 該当するものは key が空文字、unbound が true となる（一覧でグレー表示する用）
+#[tauri::command]
+fn get_shortcuts_with_unbound() -> Vec<NormalizedShortcut> {
+    let config = load_keybindings_config();
+    normalize_shortcuts_with(
+        config.iter().filter(|app| app.is_available()).cloned(),
+        &OsType::current(),
+        true,
+        load_settings().key_display_style,
+    )
+}
+
+// 指定したアプリのショートカット一覧を取得するコマンド（アプリ名は大文字小文字無視で比較）
+#[tauri::command]
+fn get_shortcuts_for_app(app: String) -> Vec<NormalizedShortcut> {
+    let app = app.to_lowercase();
+    let config = load_keybindings_config();
+    normalize_shortcuts(
+        config
+            .iter()
+            .filter(|config| config.is_available() && config.get_name().to_lowercase() == app)
+            .cloned(),
+        load_settings().key_display_style,
+    )
+}
+
+// 指定したアプリのショートカットを分類ごとに取得するコマンド
+// 分類は設定ファイルでの出現順、分類なしは空文字として最後にまとめる
+#[tauri::command]
+fn get_shortcuts_grouped(app: String) -> Vec<(String, Vec<NormalizedShortcut>)> {
+    group_by_category(get_shortcuts_for_app(app))
+}
+
+/// ショートカットを分類ごとにまとめる（分類は出現順、分類なしは空文字として最後）
+fn group_by_category(shortcuts: Vec<NormalizedShortcut>) -> Vec<(String, Vec<NormalizedShortcut>)> {
+    let mut groups: Vec<(String, Vec<NormalizedShortcut>)> = Vec::new();
+    let mut uncategorized = Vec::new();
+    for shortcut in shortcuts {
+        let Some(category) = shortcut.category.clone() else {
+            uncategorized.push(shortcut);
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == category) {
+            Some((_, shortcuts)) => shortcuts.push(shortcut),
+            None => groups.push((category, vec![shortcut])),
         }
     }
+    if !uncategorized.is_empty() {
+        groups.push((String::new(), uncategorized));
+    }
+    groups
+}
 
-    key_code.map(|code| Shortcut::new(Some(modifiers), code))
+// 指定したプラットフォームのショートカット一覧を取得するコマンド（他OSのプレビュー用）
+#[tauri::command]
+fn get_platform_shortcuts(platform: String) -> Vec<NormalizedShortcut> {
+    let Some(os) = OsType::from_name(&platform) else {
+        return Vec::new();
+    };
+    let config = load_keybindings_config();
+
+    // 実行中のOSに関係なく、指定したプラットフォームで有効なアプリのみ
+    // キーも指定したプラットフォームのものを表示
+    normalize_shortcuts_with(
+        apps_for_platform(&config, &os),
+        &os,
+        false,
+        load_settings().key_display_style,
+    )
 }
 
-#[allow(clippy::too_many_lines)] // Tauri 2.0のセットアップパターンに従う
-fn main() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
-            // 2つ目のインスタンスが起動しようとした時、既存ウィンドウを表示
-            if let Some(window) = app.get_webview_window("search") {
-                let _ = window.show();
-                let _ = window.set_focus();
-            }
-        }))
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .plugin(tauri_plugin_process::init())
-        .setup(|app| {
-            let app_handle = app.handle().clone();
+/// 指定したプラットフォームで有効なアプリのみを抽出
+fn apps_for_platform(config: &[AppConfig], os: &OsType) -> Vec<AppConfig> {
+    config
+        .iter()
+        .filter(|app| app.is_available_on(os))
+        .cloned()
+        .collect()
+}
 
-            // システムトレイを設定
-            let show_item = MenuItem::with_id(app, "show", "ウィンドウを表示", true, None::<&str>)?;
-            let keybindings_item =
-                MenuItem::with_id(app, "keybindings", "キーバインド設定", true, None::<&str>)?;
-            let config_item =
-                MenuItem::with_id(app, "config", "設定ファイルを開く", true, None::<&str>)?;
-            let update_item =
-                MenuItem::with_id(app, "update", "アップデートを確認", true, None::<&str>)?;
-            let about_item = MenuItem::with_id(app, "about", "About", true, None::<&str>)?;
-            let quit_item = MenuItem::with_id(app, "quit", "終了", true, None::<&str>)?;
-
-            let menu = Menu::with_items(
-                app,
-                &[
-                    &show_item,
-                    &keybindings_item,
-                    &config_item,
-                    &update_item,
-                    &about_item,
-                    &quit_item,
-                ],
-            )?;
+/// ショートカットの各項目（アクション・キー・アプリ名・タグ）をあいまい検索し、最高スコアを返す
+fn fuzzy_score(matcher: &SkimMatcherV2, shortcut: &NormalizedShortcut, query: &str) -> Option<i64> {
+    [&shortcut.action, &shortcut.key, &shortcut.app]
+        .into_iter()
+        .chain(&shortcut.tags)
+        .filter_map(|text| matcher.fuzzy_match(text, query))
+        .max()
+}
 
-            let app_handle_for_tray = app_handle.clone();
-            TrayIconBuilder::new()
-                .icon(app.default_window_icon().unwrap().clone())
-                .icon_as_template(true)
-                .menu(&menu)
-                .on_tray_icon_event(move |_tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        toggle_window(&app_handle_for_tray);
-                    }
-                })
-                .on_menu_event(move |app, event| match event.id.as_ref() {
-                    "show" => {
-                        toggle_window(app);
-                    }
-                    "keybindings" => {
-                        if let Some(window) = app.get_webview_window("keybindings") {
-                            let _ = window.center();
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "config" => {
-                        let _ = open_config_file();
-                    }
-                    "update" => {
-                        if let Some(window) = app.get_webview_window("search") {
-                            let _ = window.emit("check-update", ());
-                        }
-                    }
-                    "about" => {
-                        if let Some(window) = app.get_webview_window("about") {
-                            let _ = window.center();
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "quit" => {
-                        // Tauri 2.0: AppHandle::exit() でアプリケーションを正常終了
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .build(app)?;
+// ショートカットをあいまい検索するコマンド（スコアの高い順）
+#[tauri::command]
+fn search_shortcuts(query: String) -> Vec<ScoredShortcut> {
+    let shortcuts = get_shortcuts();
+    let query = query.trim();
+
+    // 空のクエリは全件を通常の順序で返す
+    if query.is_empty() {
+        return shortcuts
+            .into_iter()
+            .map(|shortcut| ScoredShortcut { shortcut, score: 0 })
+            .collect();
+    }
 
-            // バックグラウンドでアクティブウィンドウを監視開始
-            start_active_window_monitor();
+    let matcher = SkimMatcherV2::default();
+    let mut results: Vec<ScoredShortcut> = shortcuts
+        .into_iter()
+        .filter_map(|shortcut| {
+            let score = fuzzy_score(&matcher, &shortcut, query)?;
+            Some(ScoredShortcut { shortcut, score })
+        })
+        .collect();
+    // 同スコアは設定順を維持（安定ソート）
+    results.sort_by_key(|result| std::cmp::Reverse(result.score));
+    results
+}
 
-            // 設定からホットキーを読み込み
-            let settings = load_settings();
-            let hotkey = &settings.hotkey;
+// suggest_action で候補とみなす類似度の下限（0.0〜1.0）
+const SUGGESTION_MIN_SIMILARITY: f64 = 0.6;
+
+/// 2つの文字列の編集距離（レーベンシュタイン距離、文字単位）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
 
-            // グローバルホットキーを登録
-            if let Some(shortcut) = parse_hotkey(hotkey) {
-                if let Err(e) = app.global_shortcut().on_shortcut(shortcut, move |_app, _shortcut, event| {
-                    if event.state == ShortcutState::Pressed {
-                        toggle_window(&app_handle);
-                    }
-                }) {
-                    eprintln!("Warning: Failed to register global hotkey ({hotkey}): {e:?}");
-                }
+/// 2つの文字列の類似度（1.0で一致、大文字小文字無視）
+#[allow(clippy::cast_precision_loss)] // 文字数は f64 で十分表現できる
+fn similarity(a: &str, b: &str) -> f64 {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / max_len as f64
+}
+
+// クエリに最も近いアクション名のショートカットを取得するコマンド（「もしかして」表示用）
+// 十分に近いものがなければ None
+#[tauri::command]
+fn suggest_action(query: String) -> Option<NormalizedShortcut> {
+    closest_action(query.trim(), get_shortcuts())
+}
+
+/// アクション名がクエリに最も近いショートカットを選ぶ（類似度が下限未満のものは対象外）
+fn closest_action(query: &str, shortcuts: Vec<NormalizedShortcut>) -> Option<NormalizedShortcut> {
+    if query.is_empty() {
+        return None;
+    }
+    shortcuts
+        .into_iter()
+        .map(|shortcut| (similarity(query, &shortcut.action), shortcut))
+        .filter(|(score, _)| *score >= SUGGESTION_MIN_SIMILARITY)
+        // 同じ類似度なら設定順で先のものを優先
+        .reduce(|best, candidate| {
+            if candidate.0 > best.0 {
+                candidate
             } else {
-                eprintln!("Warning: Failed to parse hotkey: {hotkey}");
+                best
             }
+        })
+        .map(|(_, shortcut)| shortcut)
+}
 
-            // 初期表示
-            if let Some(window) = app.get_webview_window("search") {
-                WINDOW_VISIBLE.store(true, Ordering::SeqCst);
-                let _ = window.center();
-                let _ = window.show();
-                let _ = window.set_focus();
+/// タグでショートカットを絞り込む
+/// `match_all`がtrueなら全タグを含むもの（AND）、falseならいずれかを含むもの（OR）
+/// タグ指定が空の場合は絞り込まない
+fn filter_by_tags(
+    shortcuts: Vec<NormalizedShortcut>,
+    tags: &[String],
+    match_all: bool,
+) -> Vec<NormalizedShortcut> {
+    if tags.is_empty() {
+        return shortcuts;
+    }
+    shortcuts
+        .into_iter()
+        .filter(|shortcut| {
+            let has_tag = |tag: &String| shortcut.tags.contains(tag);
+            if match_all {
+                tags.iter().all(has_tag)
+            } else {
+                tags.iter().any(has_tag)
             }
-
-            Ok(())
         })
-        .on_window_event(|window, event| {
-            // 検索ウィンドウのみ処理（キーガイドウィンドウは除外）
-            if window.label() != "search" {
-                return;
+        .collect()
+}
+
+// タグでショートカットを絞り込むコマンド
+#[tauri::command]
+fn get_shortcuts_by_tags(tags: Vec<String>, match_all: bool) -> Vec<NormalizedShortcut> {
+    filter_by_tags(get_shortcuts(), &tags, match_all)
+}
+
+/// タグごとの使用数を集計（使用数の多い順、同数はタグ名順）
+fn count_tags(shortcuts: &[NormalizedShortcut]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tag in shortcuts.iter().flat_map(|shortcut| &shortcut.tags) {
+        *counts.entry(tag.as_str()).or_default() += 1;
+    }
+    let mut tags: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(tag, count)| (tag.to_string(), count))
+        .collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    tags
+}
+
+// 全タグと使用数の一覧を取得するコマンド
+#[tauri::command]
+fn get_all_tags() -> Vec<(String, usize)> {
+    count_tags(&get_shortcuts())
+}
+
+/// ショートカット一覧からキーごとの使用数を集計（修飾キーと通常のキーは別々に集計）
+fn count_key_usage(
+    shortcuts: &[NormalizedShortcut],
+) -> (BTreeMap<String, usize>, BTreeMap<String, usize>) {
+    let mut keys = BTreeMap::new();
+    let mut modifiers = BTreeMap::new();
+    let parts = shortcuts
+        .iter()
+        .flat_map(|shortcut| shortcut.key.split(" → "))
+        .flat_map(|step| step.split(" + "))
+        .map(str::trim)
+        .filter(|part| !part.is_empty());
+    for part in parts {
+        match canonical_modifier(part) {
+            Some(modifier) => *modifiers.entry(modifier.to_string()).or_default() += 1,
+            None => *keys.entry(part.to_uppercase()).or_default() += 1,
+        }
+    }
+    (keys, modifiers)
+}
+
+// キーごとの使用数をヒートマップ用のJSONとして取得するコマンド
+// 例: { "keys": { "K": 3, "F12": 2 }, "modifiers": { "ctrl": 10, "shift": 4 } }
+#[tauri::command]
+fn export_heatmap() -> serde_json::Value {
+    let (keys, modifiers) = count_key_usage(&get_shortcuts());
+    serde_json::json!({ "keys": keys, "modifiers": modifiers })
+}
+
+/// キー文字列が指定したトークンを独立した要素として含むか（大文字小文字無視）
+/// "+"・空白・"→" で区切って比較するため、"F5" は "Ctrl + F5" にマッチし "F50" にはマッチしない
+fn key_contains_token(key: &str, token: &str) -> bool {
+    key.split(|c: char| c == '+' || c == '→' || c.is_whitespace())
+        .any(|segment| !segment.is_empty() && segment.eq_ignore_ascii_case(token))
+}
+
+// 指定したキーを使うショートカットをすべて取得するコマンド（例: "F5" → "Ctrl + F5" など）
+#[tauri::command]
+fn find_by_key_token(token: String) -> Vec<NormalizedShortcut> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Vec::new();
+    }
+    get_shortcuts()
+        .into_iter()
+        .filter(|shortcut| key_contains_token(&shortcut.key, token))
+        .collect()
+}
+
+/// 全キーバインドのタグを置換（置換後のタグが既にある場合は重複させない）
+/// 戻り値は変更されたキーバインドの数
+fn rename_tag_in_config(config: &mut [AppConfig], old: &str, new: &str) -> usize {
+    let mut changed = 0;
+    for keybinding in config.iter_mut().flat_map(|app| app.keybindings.iter_mut()) {
+        if !keybinding.tags.iter().any(|tag| tag == old) {
+            continue;
+        }
+        let already_has_new = keybinding.tags.iter().any(|tag| tag == new);
+        let mut replaced = false;
+        keybinding.tags.retain_mut(|tag| {
+            if tag != old {
+                return true;
+            }
+            // 最初の1つだけ置換し、残りは重複として削除
+            if already_has_new || replaced {
+                return false;
             }
+            new.clone_into(tag);
+            replaced = true;
+            true
+        });
+        changed += 1;
+    }
+    changed
+}
 
-            match event {
-                // フォーカスを失ったらウィンドウを非表示
-                WindowEvent::Focused(focused) => {
-                    if !focused {
-                        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
-                        let _ = window.hide();
-                    }
-                }
-                // 閉じるボタンでアプリを終了せず、ウィンドウを非表示にする
-                WindowEvent::CloseRequested { api, .. } => {
-                    api.prevent_close();
-                    WINDOW_VISIBLE.store(false, Ordering::SeqCst);
-                    let _ = window.hide();
-                }
-                _ => {}
+// タグを一括で名前変更するコマンド
+#[tauri::command]
+fn rename_tag(old: String, new: String) -> Result<usize, String> {
+    let new = new.trim();
+    if new.is_empty() {
+        return Err("新しいタグ名が空です".to_string());
+    }
+    if old == new {
+        return Ok(0);
+    }
+
+    let mut config = load_user_keybindings_config().to_vec();
+    let changed = rename_tag_in_config(&mut config, &old, new);
+    if changed > 0 {
+        save_keybindings_config(&config)?;
+    }
+    Ok(changed)
+}
+
+/// 修飾キー名を正規化（ctrl / shift / alt / meta）
+fn canonical_modifier(part: &str) -> Option<&'static str> {
+    match part.trim().to_lowercase().as_str() {
+        "ctrl" | "control" | "⌃" => Some("ctrl"),
+        "shift" | "⇧" => Some("shift"),
+        "alt" | "option" | "⌥" => Some("alt"),
+        "cmd" | "command" | "super" | "win" | "meta" | "⌘" => Some("meta"),
+        _ => None,
+    }
+}
+
+/// 同時押しキー文字列を修飾キーの集合と本体キーに分解
+/// 順次入力キー（"->"）や修飾キー以外が複数含まれる場合はNone
+fn split_key_combo(key: &str) -> Option<(BTreeSet<&'static str>, String)> {
+    if key.contains("->") {
+        return None;
+    }
+    let normalized = normalize_hotkey_for_tauri(key);
+    let (modifier_part, main_key) = match normalized.rsplit_once('+') {
+        // "Ctrl++" のように本体キー自体が "+" の場合
+        Some((rest, "")) => (rest.strip_suffix('+')?, "+"),
+        Some((rest, main_key)) => (rest, main_key),
+        None => ("", normalized.as_str()),
+    };
+    let modifiers = modifier_part
+        .split('+')
+        .filter(|part| !part.is_empty())
+        .map(canonical_modifier)
+        .collect::<Option<BTreeSet<_>>>()?;
+    Some((modifiers, main_key.trim().to_string()))
+}
+
+/// 指定した修飾キーの組み合わせでA〜Zのうち何文字が割り当て済みかの割合を計算
+#[allow(clippy::cast_precision_loss)] // 文字数は最大26なので精度損失なし
+fn calculate_coverage(app: &AppConfig, modifier: &str) -> f64 {
+    let Some(target) = modifier
+        .split('+')
+        .filter(|part| !part.trim().is_empty())
+        .map(canonical_modifier)
+        .collect::<Option<BTreeSet<_>>>()
+    else {
+        return 0.0;
+    };
+    let letters: BTreeSet<char> = app
+        .keybindings
+        .iter()
+        .filter_map(|kb| kb.get_key(&OsType::current()))
+        .filter_map(|key| split_key_combo(&key))
+        .filter(|(modifiers, _)| *modifiers == target)
+        .filter_map(|(_, main_key)| {
+            let mut chars = main_key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_alphabetic() => Some(c.to_ascii_uppercase()),
+                _ => None,
             }
         })
-        .invoke_handler(tauri::generate_handler![
-            hide_main_window,
-            get_platform,
-            get_matched_apps,
-            get_shortcuts,
-            open_config_file,
-            open_settings_file,
-            open_keybindings_window,
-            close_keybindings_window,
-            get_app_version,
-            open_about_window,
+        .collect();
+    letters.len() as f64 / 26.0
+}
+
+// 現在のプラットフォームで有効なアプリの概要一覧を取得するコマンド（名前順）
+#[tauri::command]
+fn get_app_summaries() -> Vec<AppSummary> {
+    let config = load_keybindings_config();
+    let mut apps: Vec<&AppConfig> = config.iter().filter(|app| app.is_available()).collect();
+    apps.sort_by_cached_key(|app| app.sort_key());
+    apps.iter()
+        .map(|app| AppSummary {
+            name: app.get_name(),
+            icon: app.get_icon(),
+            shortcut_count: app.keybindings.len(),
+        })
+        .collect()
+}
+
+// アプリの修飾キーごとのキー割り当て率（A〜Z）を取得するコマンド
+#[tauri::command]
+fn get_coverage(app_name: String, modifier: String) -> f64 {
+    load_keybindings_config()
+        .iter()
+        .filter(|app| app.is_available())
+        .find(|app| app.get_name().eq_ignore_ascii_case(&app_name))
+        .map_or(0.0, |app| calculate_coverage(app, &modifier))
+}
+
+// 使用回数を読み込む
+fn load_usage() -> Vec<UsageEntry> {
+    load_json_store(get_usage_path(), &USAGE_CACHE)
+}
+
+// ショートカットの使用を記録するコマンド
+#[tauri::command]
+fn record_shortcut_use(action: String, app: String) -> Result<(), String> {
+    update_json_store(
+        get_usage_path(),
+        &USAGE_CACHE,
+        |usage: &mut Vec<UsageEntry>| {
+            if let Some(entry) = usage
+                .iter_mut()
+                .find(|entry| entry.app == app && entry.action == action)
+            {
+                entry.count += 1;
+            } else {
+                usage.push(UsageEntry {
+                    app,
+                    action,
+                    count: 1,
+                });
+            }
+        },
+    )
+}
+
+/// 使用回数の多い順にショートカットを並べる（設定に存在しないものは除外）
+fn rank_by_usage(
+    shortcuts: Vec<NormalizedShortcut>,
+    mut usage: Vec<UsageEntry>,
+    limit: usize,
+) -> Vec<NormalizedShortcut> {
+    usage.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+    usage
+        .iter()
+        .filter_map(|entry| {
+            shortcuts
+                .iter()
+                .find(|shortcut| shortcut.app == entry.app && shortcut.action == entry.action)
+                .cloned()
+        })
+        .take(limit)
+        .collect()
+}
+
+// よく使うショートカットの一覧を取得するコマンド
+#[tauri::command]
+fn get_most_used_shortcuts(limit: usize) -> Vec<NormalizedShortcut> {
+    rank_by_usage(get_shortcuts(), load_usage(), limit)
+}
+
+// アクティブなアプリで最もよく使うショートカットをオーバーレイ表示するコマンド
+// 使用履歴がない場合はアプリの先頭のショートカットを表示
+#[tauri::command]
+fn show_top_shortcut(app: AppHandle) -> Result<(), String> {
+    let info = get_last_active_app().ok_or("アクティブなアプリが検出されていません")?;
+    let shortcuts = normalize_shortcuts(
+        matched_non_global_apps(&info, &load_keybindings_config()),
+        load_settings().key_display_style,
+    );
+    let top = pick_top_shortcut(shortcuts, load_usage())
+        .ok_or("アクティブなアプリにマッチするショートカットがありません")?;
+    show_overlay(app, top.app, top.action, top.key)
+}
+
+/// 最もよく使うショートカットを選ぶ（使用履歴がなければ先頭のショートカット）
+fn pick_top_shortcut(
+    shortcuts: Vec<NormalizedShortcut>,
+    usage: Vec<UsageEntry>,
+) -> Option<NormalizedShortcut> {
+    select_top_shortcuts(shortcuts, usage, 1).into_iter().next()
+}
+
+/// アクティブウィンドウにマッチしたアプリ（globalなアプリは常に表示されるため除く）
+fn matched_non_global_apps(info: &ActiveWindowInfo, apps: &[AppConfig]) -> Vec<AppConfig> {
+    apps_for_window(info, apps)
+        .into_iter()
+        .filter(|app| !app.global)
+        .collect()
+}
+
+// クイックリファレンスカードに載せるショートカットの数
+const QUICK_CARD_ROWS: usize = 8;
+
+/// よく使う順に最大 `limit` 件を選び、足りない分は設定順で補う
+fn select_top_shortcuts(
+    shortcuts: Vec<NormalizedShortcut>,
+    usage: Vec<UsageEntry>,
+    limit: usize,
+) -> Vec<NormalizedShortcut> {
+    let mut selected = rank_by_usage(shortcuts.clone(), usage, limit);
+    for shortcut in shortcuts {
+        if selected.len() >= limit {
+            break;
+        }
+        if !selected.contains(&shortcut) {
+            selected.push(shortcut);
+        }
+    }
+    selected
+}
+
+// 指定したアプリのクイックリファレンスカードを取得するコマンド
+// よく使うショートカット（足りない分は設定順）を最大 QUICK_CARD_ROWS 件、オーバーレイと同じ幅の計算で返す
+#[tauri::command]
+fn get_quick_card(app_name: String) -> QuickCard {
+    let width_range = load_settings().overlay_width_range();
+    let icon = load_keybindings_config()
+        .iter()
+        .find(|app| app.is_available() && app.get_name().to_lowercase() == app_name.to_lowercase())
+        .map(AppConfig::get_icon)
+        .unwrap_or_default();
+    let shortcuts = get_shortcuts_for_app(app_name.clone());
+    let rows: Vec<QuickCardRow> = select_top_shortcuts(shortcuts, load_usage(), QUICK_CARD_ROWS)
+        .into_iter()
+        .map(|shortcut| QuickCardRow {
+            width: calculate_overlay_width(&app_name, &shortcut.action, &shortcut.key, width_range),
+            action: shortcut.action,
+            key: shortcut.key,
+        })
+        .collect();
+    let width = rows
+        .iter()
+        .map(|row| row.width)
+        .fold(width_range.0, f64::max);
+    QuickCard {
+        app: app_name,
+        icon,
+        rows,
+        width,
+    }
+}
+
+// お気に入りを読み込む
+fn load_favorites() -> Vec<FavoriteEntry> {
+    load_json_store(get_favorites_path(), &FAVORITES_CACHE)
+}
+
+// お気に入りの登録/解除を切り替えるコマンド（戻り値は切り替え後の登録状態）
+#[tauri::command]
+fn toggle_favorite(app: String, action: String) -> Result<bool, String> {
+    let entry = FavoriteEntry { app, action };
+    update_json_store(
+        get_favorites_path(),
+        &FAVORITES_CACHE,
+        |favorites: &mut Vec<FavoriteEntry>| {
+            if let Some(index) = favorites.iter().position(|f| *f == entry) {
+                favorites.remove(index);
+                false
+            } else {
+                favorites.push(entry);
+                true
+            }
+        },
+    )
+}
+
+/// お気に入りを現在のショートカットに解決する（設定から削除されたものは除外）
+fn resolve_favorites(
+    shortcuts: &[NormalizedShortcut],
+    favorites: &[FavoriteEntry],
+) -> Vec<NormalizedShortcut> {
+    favorites
+        .iter()
+        .filter_map(|favorite| {
+            shortcuts
+                .iter()
+                .find(|shortcut| shortcut.app == favorite.app && shortcut.action == favorite.action)
+                .cloned()
+        })
+        .collect()
+}
+
+// お気に入りのショートカット一覧を取得するコマンド（登録順）
+#[tauri::command]
+fn get_favorites() -> Vec<NormalizedShortcut> {
+    resolve_favorites(&get_shortcuts(), &load_favorites())
+}
+
+/// ファイルをシステムのデフォルトアプリケーションで開く
+fn open_file_with_default_app(path: &std::path::Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        // explorer.exe でファイルを開く（パスのエスケープが不要）
+        std::process::Command::new("explorer")
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("ファイルを開けませんでした: {e}"))?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("ファイルを開けませんでした: {e}"))?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(path)
+            .spawn()
+            .map_err(|e| format!("ファイルを開けませんでした: {e}"))?;
+    }
+
+    Ok(())
+}
+
+// 設定フォルダをファイルマネージャーで開くコマンド（存在しなければ作成）
+#[tauri::command]
+fn open_config_dir() -> Result<(), String> {
+    let dir = get_config_dir().ok_or("設定フォルダのパスが見つかりません")?;
+    fs::create_dir_all(&dir).map_err(|e| format!("ディレクトリ作成エラー: {e}"))?;
+    open_file_with_default_app(&dir)
+}
+
+// 設定のアプリのウィンドウを最前面に表示するコマンド
+// 起動中のウィンドウが見つからなければ launch の設定で起動する
+#[tauri::command]
+fn activate_app(app: String) -> Result<(), String> {
+    let config = load_keybindings_config();
+    // 他のOS向けのアプリや無効にしたアプリは対象外
+    let app_config = config
+        .iter()
+        .find(|config| config.is_available() && config.get_name() == app)
+        .ok_or_else(|| format!("アプリが見つかりません: {app}"))?;
+    if active_window::focus_app_window(app_config) {
+        return Ok(());
+    }
+    let launch = app_config
+        .launch
+        .as_deref()
+        .map(str::trim)
+        .filter(|launch| !launch.is_empty())
+        .ok_or_else(|| {
+            format!("起動中のウィンドウが見つからず、起動方法（launch）も設定されていません: {app}")
+        })?;
+    launch_app(launch)
+}
+
+/// `launch` の設定でアプリを起動（終了は待たない）
+/// パスの場合は存在を確認してから起動し、それ以外はOSの方法でアプリ名として起動する
+/// 共有された設定で任意のコマンドが実行されないよう、シェルは経由しない
+/// 相対パスは設定フォルダを基準とする（起動時のカレントディレクトリに左右されないように）
+fn launch_app(launch: &str) -> Result<(), String> {
+    let is_path = Path::new(launch).is_absolute() || launch.contains(std::path::MAIN_SEPARATOR);
+    let path = if is_path && Path::new(launch).is_relative() {
+        get_config_dir()
+            .ok_or("設定フォルダのパスが見つかりません")?
+            .join(launch)
+    } else {
+        PathBuf::from(launch)
+    };
+    if is_path && !path.exists() {
+        return Err(format!(
+            "起動するファイルが見つかりません: {}",
+            path.display()
+        ));
+    }
+
+    // macOS: アプリのパス（.app）は open、それ以外はアプリ名として open -a
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        if !is_path {
+            command.arg("-a");
+        }
+        command.arg(&path);
+        command
+    };
+
+    // Windows: .exe は直接実行、それ以外のファイルは explorer で既定のアプリで開く
+    #[cfg(target_os = "windows")]
+    if !is_path {
+        return Err(format!(
+            "起動方法（launch）には .exe などのパスを指定してください: {launch}"
+        ));
+    }
+    #[cfg(target_os = "windows")]
+    let mut command = if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+    {
+        std::process::Command::new(&path)
+    } else {
+        let mut command = std::process::Command::new("explorer");
+        command.arg(&path);
+        command
+    };
+
+    // Linux: 実行ファイルは直接実行、.desktop はデスクトップエントリ名として gtk-launch
+    #[cfg(target_os = "linux")]
+    let mut command = if is_path {
+        std::process::Command::new(&path)
+    } else if let Some(entry) = launch.strip_suffix(".desktop") {
+        let mut command = std::process::Command::new("gtk-launch");
+        command.arg(entry);
+        command
+    } else {
+        return Err(format!(
+            "起動方法（launch）には実行ファイルのパスか、.desktop 付きのデスクトップエントリ名を指定してください: {launch}"
+        ));
+    };
+
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("アプリを起動できません: {launch} ({e})"))
+}
+
+// アプリのドキュメントURLを既定のブラウザで開くコマンド
+#[tauri::command]
+fn open_app_docs(app: String) -> Result<(), String> {
+    // 他のOS向けのアプリや無効にしたアプリは対象外
+    let url = load_keybindings_config()
+        .iter()
+        .find(|config| config.is_available() && config.get_name() == app)
+        .ok_or_else(|| format!("アプリが見つかりません: {app}"))?
+        .url
+        .clone()
+        .ok_or_else(|| format!("ドキュメントURLが設定されていません: {app}"))?;
+    // 設定ファイル経由で任意のコマンドを実行させないよう、http(s) のみ許可
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!(
+            "URLは http:// または https:// で始まる必要があります: {url}"
+        ));
+    }
+    open_file_with_default_app(std::path::Path::new(&url))
+}
+
+// キーバインド設定ファイルを開くコマンド
+#[tauri::command]
+fn open_config_file() -> Result<(), String> {
+    let path = get_keybindings_config_path().ok_or("設定ファイルのパスが見つかりません")?;
+    open_file_with_default_app(&path)
+}
+
+// settings.jsonファイルを開くコマンド
+#[tauri::command]
+fn open_settings_file() -> Result<(), String> {
+    let path = get_settings_path().ok_or("設定ファイルのパスが見つかりません")?;
+
+    // ファイルが存在しない場合は作成
+    if !path.exists() {
+        let settings = AppSettings::default();
+        save_settings(&settings)?;
+    }
+
+    open_file_with_default_app(&path)
+}
+
+// テーマ設定を取得
+#[tauri::command]
+fn get_theme_setting() -> String {
+    let settings = load_settings();
+    match settings.theme {
+        ThemeSetting::System => "system".to_string(),
+        ThemeSetting::Light => "light".to_string(),
+        ThemeSetting::Dark => "dark".to_string(),
+    }
+}
+
+// テーマ設定を保存
+#[tauri::command]
+fn set_theme_setting(theme: String) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.theme = match theme.as_str() {
+        "light" => ThemeSetting::Light,
+        "dark" => ThemeSetting::Dark,
+        _ => ThemeSetting::System,
+    };
+    save_settings(&settings)
+}
+
+// 言語設定を取得
+#[tauri::command]
+fn get_language() -> String {
+    let settings = load_settings();
+    match settings.language {
+        LanguageSetting::System => "system".to_string(),
+        LanguageSetting::Ja => "ja".to_string(),
+        LanguageSetting::En => "en".to_string(),
+    }
+}
+
+// 言語設定を保存（トレイメニューも更新）
+#[tauri::command]
+fn set_language(app: AppHandle, language: String) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.language = match language.as_str() {
+        "ja" => LanguageSetting::Ja,
+        "en" => LanguageSetting::En,
+        _ => LanguageSetting::System,
+    };
+    save_settings(&settings)?;
+    update_tray_tooltip(&app);
+    refresh_tray_menu(&app)
+}
+
+/// トレイメニューを作成（設定の言語で表示）
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let labels = load_settings().language.resolve().tray_labels();
+
+    // 現在のアクティブウィンドウにマッチしているアプリ（検出状況の確認用）
+    let matched_apps = get_matched_apps(get_last_active_app());
+    if let Ok(mut names) = TRAY_MATCHED_APPS.lock() {
+        *names = matched_apps
+            .iter()
+            .map(|matched| matched.name.clone())
+            .collect();
+    }
+    let matched_items = if matched_apps.is_empty() {
+        vec![MenuItem::with_id(
+            app,
+            MATCHED_APP_MENU_PREFIX,
+            labels.no_detected_apps,
+            false,
+            None::<&str>,
+        )?]
+    } else {
+        matched_apps
+            .iter()
+            .map(|matched| {
+                let label = format!("{} {}", matched.icon, matched.name);
+                let id = format!("{MATCHED_APP_MENU_PREFIX}{}", matched.name);
+                MenuItem::with_id(app, id, label.trim(), true, None::<&str>)
+            })
+            .collect::<tauri::Result<Vec<_>>>()?
+    };
+    let matched_item_refs: Vec<&dyn IsMenuItem<tauri::Wry>> = matched_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<tauri::Wry>)
+        .collect();
+    let detected_submenu =
+        Submenu::with_items(app, labels.detected_apps, true, &matched_item_refs)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+
+    let show_item = MenuItem::with_id(app, "show", labels.show, true, None::<&str>)?;
+    let pin_item = CheckMenuItem::with_id(
+        app,
+        "pin",
+        labels.pin,
+        true,
+        WINDOW_PINNED.load(Ordering::SeqCst),
+        None::<&str>,
+    )?;
+    let keybindings_item =
+        MenuItem::with_id(app, "keybindings", labels.keybindings, true, None::<&str>)?;
+    let config_item = MenuItem::with_id(app, "config", labels.config, true, None::<&str>)?;
+    let update_item = MenuItem::with_id(app, "update", labels.update, true, None::<&str>)?;
+    let about_item = MenuItem::with_id(app, "about", labels.about, true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", labels.quit, true, None::<&str>)?;
+
+    Menu::with_items(
+        app,
+        &[
+            &detected_submenu,
+            &separator,
+            &show_item,
+            &pin_item,
+            &keybindings_item,
+            &config_item,
+            &update_item,
+            &about_item,
+            &quit_item,
+        ],
+    )
+}
+
+/// トレイメニューを作り直す（言語変更時、固定表示の切り替え時）
+fn refresh_tray_menu(app: &AppHandle) -> Result<(), String> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let menu =
+        build_tray_menu(app).map_err(|e| format!("トレイメニューの作成に失敗しました: {e}"))?;
+    tray.set_menu(Some(menu))
+        .map_err(|e| format!("トレイメニューの更新に失敗しました: {e}"))
+}
+
+/// 検出中のアプリが前回メニューを作成した時から変わっていればトレイメニューを作り直す
+fn refresh_tray_matched_apps(app: &AppHandle) -> Result<(), String> {
+    let names: Vec<String> = get_matched_apps(get_last_active_app())
+        .into_iter()
+        .map(|matched| matched.name)
+        .collect();
+    if TRAY_MATCHED_APPS.lock().is_ok_and(|built| *built == names) {
+        return Ok(());
+    }
+    refresh_tray_menu(app)
+}
+
+// 主表示の設定を取得
+#[tauri::command]
+fn get_primary_label() -> PrimaryLabel {
+    load_settings().primary_label
+}
+
+// 主表示の設定を保存（全ウィンドウに通知）
+#[tauri::command]
+fn set_primary_label(app: AppHandle, primary_label: PrimaryLabel) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.primary_label = primary_label;
+    save_settings(&settings)?;
+    let _ = app.emit("primary-label-changed", primary_label);
+    Ok(())
+}
+
+// Esc キーで検索ウィンドウを閉じるかどうかを取得
+#[tauri::command]
+fn get_escape_to_hide() -> bool {
+    load_settings().escape_to_hide
+}
+
+// Esc キーで検索ウィンドウを閉じるかどうかを保存（次にウィンドウを表示した時から反映）
+#[tauri::command]
+fn set_escape_to_hide(enabled: bool) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.escape_to_hide = enabled;
+    save_settings(&settings)
+}
+
+/// ショートカット一覧をタブ区切りテキストに変換（列順: アプリ、主表示、副表示）
+fn format_shortcuts_text(shortcuts: &[NormalizedShortcut], primary_label: PrimaryLabel) -> String {
+    shortcuts
+        .iter()
+        .map(|shortcut| {
+            let (primary, secondary) = match primary_label {
+                PrimaryLabel::Action => (&shortcut.action, &shortcut.key),
+                PrimaryLabel::Key => (&shortcut.key, &shortcut.action),
+            };
+            format!("{}\t{primary}\t{secondary}", shortcut.app)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ショートカット一覧をテキストで出力するコマンド（主表示の設定に従った列順）
+#[tauri::command]
+fn export_shortcuts_text() -> String {
+    format_shortcuts_text(&get_shortcuts(), load_settings().primary_label)
+}
+
+/// ショートカットをアプリごとにまとめる（アプリは最初に出現した順）
+fn group_by_app(shortcuts: &[NormalizedShortcut]) -> Vec<(&str, Vec<&NormalizedShortcut>)> {
+    let mut groups: Vec<(&str, Vec<&NormalizedShortcut>)> = Vec::new();
+    for shortcut in shortcuts {
+        match groups.iter_mut().find(|(app, _)| *app == shortcut.app) {
+            Some((_, group)) => group.push(shortcut),
+            None => groups.push((&shortcut.app, vec![shortcut])),
+        }
+    }
+    groups
+}
+
+/// 有効なアプリの名前とアイコンの対応表（エクスポートの見出し用）
+fn available_app_icons() -> HashMap<String, String> {
+    load_keybindings_config()
+        .iter()
+        .filter(|app| app.is_available())
+        .map(|app| (app.get_name(), app.get_icon()))
+        .collect()
+}
+
+/// Markdownの表のセル用に文字列をエスケープ（"|" と改行）
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// ショートカット一覧をアプリごとのMarkdownの表に変換（アプリは最初に出現した順）
+/// 列順は主表示の設定に従う
+fn format_cheatsheet_markdown(
+    shortcuts: &[NormalizedShortcut],
+    app_icons: &HashMap<String, String>,
+    primary_label: PrimaryLabel,
+) -> String {
+    let groups = group_by_app(shortcuts);
+    let header = match primary_label {
+        PrimaryLabel::Action => "| アクション | キー |",
+        PrimaryLabel::Key => "| キー | アクション |",
+    };
+    let mut lines = vec!["# ショートカット一覧".to_string()];
+    for (app, group) in groups {
+        let icon = app_icons.get(app).map_or("", String::as_str);
+        let heading = if icon.is_empty() {
+            app.to_string()
+        } else {
+            format!("{icon} {app}")
+        };
+        lines.extend([
+            String::new(),
+            format!("## {heading}"),
+            String::new(),
+            header.to_string(),
+            "| --- | --- |".to_string(),
+        ]);
+        lines.extend(group.into_iter().map(|shortcut| {
+            let action = escape_markdown_cell(&shortcut.action);
+            let key = format!("`{}`", escape_markdown_cell(&shortcut.key));
+            let (primary, secondary) = match primary_label {
+                PrimaryLabel::Action => (action, key),
+                PrimaryLabel::Key => (key, action),
+            };
+            format!("| {primary} | {secondary} |")
+        }));
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+// ショートカット一覧をアプリごとのMarkdownの表として指定したファイルに書き出すコマンド
+#[tauri::command]
+fn export_cheatsheet_markdown(path: String) -> Result<(), String> {
+    let markdown = format_cheatsheet_markdown(
+        &get_shortcuts(),
+        &available_app_icons(),
+        load_settings().primary_label,
+    );
+    fs::write(&path, markdown).map_err(|e| format!("ファイル書き込みエラー（{path}）: {e}"))
+}
+
+// HTMLのチートシートに埋め込むスタイル
+const CHEATSHEET_HTML_STYLE: &str = r"
+body { font-family: -apple-system, 'Segoe UI', 'Hiragino Sans', 'Meiryo', sans-serif; margin: 2rem auto; max-width: 960px; padding: 0 1rem; color: #1d1d1f; background: #f5f5f7; }
+h1 { font-size: 1.6rem; }
+#search { width: 100%; box-sizing: border-box; padding: 0.6rem 0.8rem; font-size: 1rem; border: 1px solid #c7c7cc; border-radius: 8px; margin-bottom: 1rem; }
+details { background: #fff; border-radius: 10px; margin-bottom: 0.8rem; padding: 0.4rem 1rem; box-shadow: 0 1px 3px rgba(0, 0, 0, 0.08); }
+summary { cursor: pointer; font-size: 1.15rem; font-weight: 600; padding: 0.4rem 0; }
+h3 { font-size: 0.9rem; color: #6e6e73; margin: 0.8rem 0 0.2rem; }
+table { width: 100%; border-collapse: collapse; margin-bottom: 0.6rem; }
+th, td { text-align: left; padding: 0.35rem 0.5rem; border-bottom: 1px solid #e5e5ea; }
+th { font-size: 0.8rem; color: #6e6e73; font-weight: 500; }
+kbd { font-family: ui-monospace, 'SF Mono', Consolas, monospace; background: #f2f2f7; border: 1px solid #d1d1d6; border-radius: 4px; padding: 0.1rem 0.4rem; }
+@media (prefers-color-scheme: dark) {
+  body { color: #f5f5f7; background: #1c1c1e; }
+  details { background: #2c2c2e; }
+  #search { background: #2c2c2e; color: #f5f5f7; border-color: #48484a; }
+  th, td { border-bottom-color: #3a3a3c; }
+  kbd { background: #3a3a3c; border-color: #48484a; }
+}
+";
+
+// HTMLのチートシートに埋め込む検索処理（アクション・キー・タグで絞り込み）
+const CHEATSHEET_HTML_SCRIPT: &str = r#"
+document.getElementById("search").addEventListener("input", (event) => {
+  const query = event.target.value.trim().toLowerCase();
+  for (const row of document.querySelectorAll("tbody tr")) {
+    row.hidden = query !== "" && !row.dataset.search.includes(query);
+  }
+  for (const section of document.querySelectorAll("details")) {
+    section.hidden = section.querySelector("tbody tr:not([hidden])") === null;
+    if (query !== "") section.open = true;
+  }
+});
+"#;
+
+/// HTML用に文字列をエスケープ
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// ショートカット一覧を単体で閲覧できるHTMLに変換
+/// アプリごとに折りたたみ可能なセクションとし、アプリ内は分類ごとの表にまとめる
+fn format_cheatsheet_html(
+    shortcuts: &[NormalizedShortcut],
+    app_icons: &HashMap<String, String>,
+    primary_label: PrimaryLabel,
+) -> String {
+    let header = match primary_label {
+        PrimaryLabel::Action => "<th>アクション</th><th>キー</th>",
+        PrimaryLabel::Key => "<th>キー</th><th>アクション</th>",
+    };
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html lang=\"ja\">".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">".to_string(),
+        "<title>ショートカット一覧</title>".to_string(),
+        format!("<style>{CHEATSHEET_HTML_STYLE}</style>"),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        "<h1>ショートカット一覧</h1>".to_string(),
+        "<input type=\"search\" id=\"search\" placeholder=\"検索\" autofocus>".to_string(),
+    ];
+    for (app, group) in group_by_app(shortcuts) {
+        let icon = app_icons.get(app).map_or("", String::as_str);
+        let heading = if icon.is_empty() {
+            escape_html(app)
+        } else {
+            format!("{} {}", escape_html(icon), escape_html(app))
+        };
+        lines.push(format!("<details open>\n<summary>{heading}</summary>"));
+        for (category, items) in group_by_category(group.into_iter().cloned().collect()) {
+            if !category.is_empty() {
+                lines.push(format!("<h3>{}</h3>", escape_html(&category)));
+            }
+            lines.push(format!(
+                "<table>\n<thead><tr>{header}</tr></thead>\n<tbody>"
+            ));
+            lines.extend(items.iter().map(|shortcut| {
+                let search = [&shortcut.action, &shortcut.key]
+                    .into_iter()
+                    .chain(&shortcut.tags)
+                    .map(|text| text.to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let action = format!("<td>{}</td>", escape_html(&shortcut.action));
+                let key = format!("<td><kbd>{}</kbd></td>", escape_html(&shortcut.key));
+                let (primary, secondary) = match primary_label {
+                    PrimaryLabel::Action => (action, key),
+                    PrimaryLabel::Key => (key, action),
+                };
+                format!(
+                    "<tr data-search=\"{}\">{primary}{secondary}</tr>",
+                    escape_html(&search)
+                )
+            }));
+            lines.push("</tbody>\n</table>".to_string());
+        }
+        lines.push("</details>".to_string());
+    }
+    lines.extend([
+        format!("<script>{CHEATSHEET_HTML_SCRIPT}</script>"),
+        "</body>".to_string(),
+        "</html>".to_string(),
+        String::new(),
+    ]);
+    lines.join("\n")
+}
+
+// ショートカット一覧を検索付きのHTMLとして指定したファイルに書き出すコマンド（外部ファイルに依存しない単体のHTML）
+#[tauri::command]
+fn export_cheatsheet_html(path: String) -> Result<(), String> {
+    let html = format_cheatsheet_html(
+        &get_shortcuts(),
+        &available_app_icons(),
+        load_settings().primary_label,
+    );
+    fs::write(&path, html).map_err(|e| format!("ファイル書き込みエラー（{path}）: {e}"))
+}
+
+// システムテーマを取得（ウィンドウから）
+#[tauri::command]
+fn get_system_theme(window: WebviewWindow) -> String {
+    if matches!(window.theme(), Ok(tauri::Theme::Dark)) {
+        "dark".to_string()
+    } else {
+        "light".to_string()
+    }
+}
+
+// オーバーレイ表示用のペイロード
+#[derive(Clone, Serialize)]
+struct OverlayPayload {
+    app_name: String,
+    action_name: String,
+    shortcut_key: String,
+    duration: u32,
+    theme: String,
+    /// 表示元アプリの色（未設定の場合は枠線なし）
+    app_color: Option<String>,
+    /// フェードアウトの長さ（ミリ秒、"overlay-fade-out" を受け取ってから）
+    fade_ms: u64,
+}
+
+// オーバーレイのレイアウト定数（論理ピクセル）
+const OVERLAY_BASE_WIDTH: f64 = 150.0;
+const OVERLAY_MODIFIER_WIDTH: f64 = 50.0;
+const OVERLAY_SEPARATOR_WIDTH: f64 = 20.0;
+const OVERLAY_SEQUENCE_SEPARATOR_WIDTH: f64 = 30.0;
+const OVERLAY_DEFAULT_KEY_WIDTH: f64 = 30.0;
+const OVERLAY_WIDE_CHAR_EXTRA_WIDTH: f64 = 15.0;
+// 上段（アプリ名 - アクション名）の余白・区切りの幅と、表示幅1桁あたりの幅
+const OVERLAY_INFO_BASE_WIDTH: f64 = 80.0;
+const OVERLAY_INFO_COLUMN_WIDTH: f64 = 8.0;
+const OVERLAY_MIN_WIDTH: f64 = 200.0;
+const OVERLAY_MAX_WIDTH: f64 = 600.0;
+const OVERLAY_HEIGHT: f64 = 150.0;
+// フェードアウトの長さと、フェードの完了を待ってから非表示にするまでの余裕（ミリ秒）
+const OVERLAY_FADE_MS: u64 = 200;
+const OVERLAY_HIDE_TAIL_MS: u64 = 100;
+// 設定で指定できるオーバーレイ幅の範囲
+const OVERLAY_WIDTH_LIMIT_MIN: f64 = 100.0;
+const OVERLAY_WIDTH_LIMIT_MAX: f64 = 3000.0;
+
+// フロントエンドに渡すオーバーレイのレイアウト定数
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayConstants {
+    pub base_width: f64,
+    pub modifier_width: f64,
+    pub separator_width: f64,
+    pub sequence_separator_width: f64,
+    pub default_key_width: f64,
+    pub wide_char_extra_width: f64,
+    pub info_base_width: f64,
+    pub info_column_width: f64,
+    pub min_width: f64,
+    pub max_width: f64,
+    pub height: f64,
+}
+
+// オーバーレイのレイアウト定数を取得するコマンド（フロントエンドとの幅計算の同期用）
+// 最小幅・最大幅は設定を反映した値
+#[tauri::command]
+fn get_overlay_layout_constants() -> OverlayConstants {
+    let (min_width, max_width) = load_settings().overlay_width_range();
+    OverlayConstants {
+        base_width: OVERLAY_BASE_WIDTH,
+        modifier_width: OVERLAY_MODIFIER_WIDTH,
+        separator_width: OVERLAY_SEPARATOR_WIDTH,
+        sequence_separator_width: OVERLAY_SEQUENCE_SEPARATOR_WIDTH,
+        default_key_width: OVERLAY_DEFAULT_KEY_WIDTH,
+        wide_char_extra_width: OVERLAY_WIDE_CHAR_EXTRA_WIDTH,
+        info_base_width: OVERLAY_INFO_BASE_WIDTH,
+        info_column_width: OVERLAY_INFO_COLUMN_WIDTH,
+        min_width,
+        max_width,
+        height: OVERLAY_HEIGHT,
+    }
+}
+
+/// オーバーレイの最小幅・最大幅が有効か検証（最小 ≦ 最大、かつ指定可能な範囲内）
+fn validate_overlay_width_range(min: f64, max: f64) -> Result<(), String> {
+    let limits = OVERLAY_WIDTH_LIMIT_MIN..=OVERLAY_WIDTH_LIMIT_MAX;
+    if !limits.contains(&min) || !limits.contains(&max) {
+        return Err(format!(
+            "オーバーレイの幅は {OVERLAY_WIDTH_LIMIT_MIN}〜{OVERLAY_WIDTH_LIMIT_MAX} の範囲で指定してください"
+        ));
+    }
+    if min > max {
+        return Err(format!(
+            "オーバーレイの最小幅（{min}）が最大幅（{max}）を超えています"
+        ));
+    }
+    Ok(())
+}
+
+// オーバーレイの最小幅・最大幅を保存（None で既定値に戻す）
+#[tauri::command]
+fn set_overlay_width_range(min_width: Option<f64>, max_width: Option<f64>) -> Result<(), String> {
+    validate_overlay_width_range(
+        min_width.unwrap_or(OVERLAY_MIN_WIDTH),
+        max_width.unwrap_or(OVERLAY_MAX_WIDTH),
+    )?;
+    let mut settings = load_settings();
+    settings.overlay_min_width = min_width;
+    settings.overlay_max_width = max_width;
+    save_settings(&settings)
+}
+
+/// オーバーレイウィンドウの幅を計算
+/// キーの表示と上段（アプリ名 - アクション名）のうち広い方に合わせる
+#[allow(clippy::cast_precision_loss)] // ステップ数・区切り文字数・全角文字数は小さな整数なので精度損失なし
+fn calculate_overlay_width(
+    app_name: &str,
+    action_name: &str,
+    shortcut_key: &str,
+    (min_width, max_width): (f64, f64),
+) -> f64 {
+    let mut width = OVERLAY_BASE_WIDTH;
+
+    // 順次入力キーの場合、各ステップを分割して計算
+    let steps: Vec<&str> = shortcut_key.split(" → ").collect();
+    let is_sequence = steps.len() > 1;
+
+    for step in &steps {
+        let step_lower = step.to_lowercase();
+
+        // 修飾キーの幅を加算（各ステップごとにカウント）
+        if step_lower.contains("ctrl") || step_lower.contains("control") || step.contains('⌃') {
+            width += OVERLAY_MODIFIER_WIDTH;
+        }
+        if step_lower.contains("shift") || step.contains('⇧') {
+            width += OVERLAY_MODIFIER_WIDTH;
+        }
+        if step_lower.contains("alt") || step_lower.contains("option") || step.contains('⌥') {
+            width += OVERLAY_MODIFIER_WIDTH;
+        }
+        if step_lower.contains("win")
+            || step_lower.contains("command")
+            || step_lower.contains("cmd")
+            || step.contains('⌘')
+        {
+            width += OVERLAY_MODIFIER_WIDTH;
+        }
+
+        // 同時押し区切り文字の幅を加算
+        let separator_count = step.matches('+').count();
+        width += (separator_count as f64) * OVERLAY_SEPARATOR_WIDTH;
+
+        // キー自体の幅を加算
+        width += OVERLAY_DEFAULT_KEY_WIDTH;
+
+        // 絵文字などの全角文字は表示幅が広いため追加で加算（ASCIIは影響なし）
+        let wide_char_count = step.chars().filter(|c| c.width() == Some(2)).count();
+        width += (wide_char_count as f64) * OVERLAY_WIDE_CHAR_EXTRA_WIDTH;
+    }
+
+    // 順次入力の区切り文字（→）の幅を加算
+    if is_sequence {
+        width += ((steps.len() - 1) as f64) * OVERLAY_SEQUENCE_SEPARATOR_WIDTH;
+    }
+
+    // 上段の方が広ければその幅を使用
+    width = width.max(calculate_info_width(app_name, action_name));
+
+    // 最小・最大幅でクランプ
+    width.clamp(min_width, max_width)
+}
+
+/// オーバーレイ上段（アプリ名 - アクション名）の幅を計算
+/// 文字数ではなく表示幅で数える（全角文字は2桁）
+#[allow(clippy::cast_precision_loss)] // 表示幅は小さな整数なので精度損失なし
+fn calculate_info_width(app_name: &str, action_name: &str) -> f64 {
+    let columns = app_name.width() + action_name.width();
+    (columns as f64).mul_add(OVERLAY_INFO_COLUMN_WIDTH, OVERLAY_INFO_BASE_WIDTH)
+}
+
+/// Windowsでフォーカスを奪わずにウィンドウを表示
+#[cfg(target_os = "windows")]
+#[allow(unsafe_code)]
+fn show_window_no_focus(window: &WebviewWindow) {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SetWindowPos, ShowWindow, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+        SW_SHOWNOACTIVATE,
+    };
+
+    // HWNDを取得
+    if let Ok(hwnd) = window.hwnd() {
+        let hwnd = windows::Win32::Foundation::HWND(hwnd.0.cast());
+
+        // SAFETY: 以下のWindows API呼び出しは安全です：
+        // - ShowWindow: 有効なHWND（Tauriから取得）に対してウィンドウの表示状態を変更
+        //   SW_SHOWNOACTIVATEはフォーカスを移動せずにウィンドウを表示
+        // - SetWindowPos: HWND_TOPMOSTでZオーダーを最前面に設定
+        //   SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZEにより位置/サイズ変更なし
+        unsafe {
+            // SW_SHOWNOACTIVATEでフォーカスを奪わずに表示
+            let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+
+            // 常に最前面に配置（フォーカスは奪わない）
+            let _ = SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZE,
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn show_window_no_focus(window: &WebviewWindow) {
+    let _ = window.show();
+}
+
+/// "HH:MM" 形式の時刻を0時からの分数に変換
+fn parse_time_of_day(value: &str) -> Option<u32> {
+    let (hour, minute) = value.trim().split_once(':')?;
+    let hour = hour.parse::<u32>().ok().filter(|hour| *hour < 24)?;
+    let minute = minute.parse::<u32>().ok().filter(|minute| *minute < 60)?;
+    Some(hour * 60 + minute)
+}
+
+/// 時刻（0時からの分数）が時間帯に含まれるか（終了は含まない）
+/// 開始が終了より後の場合は日をまたぐ時間帯（例: 22:00〜07:00）として扱う
+const fn is_within_time_range(now: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// 現在時刻がオーバーレイを表示しない時間帯かどうか
+fn is_quiet_hours(settings: &AppSettings) -> bool {
+    let Some((start, end)) = settings.quiet_hours() else {
+        return false;
+    };
+    let now = chrono::Local::now();
+    is_within_time_range(now.hour() * 60 + now.minute(), start, end)
+}
+
+/// 表示名が一致するアプリに設定された色を取得
+fn find_app_color(apps: &[AppConfig], app_name: &str) -> Option<String> {
+    apps.iter()
+        .find(|app| app.get_name() == app_name)
+        .and_then(|app| app.color.clone())
+}
+
+// オーバーレイウィンドウを表示
+#[tauri::command]
+#[allow(clippy::unnecessary_wraps)] // フロントエンドとの互換性のため Result を返す
+fn show_overlay(
+    app: AppHandle,
+    app_name: String,
+    action_name: String,
+    shortcut_key: String,
+) -> Result<(), String> {
+    let settings = load_settings();
+    // 表示しない時間帯は何もしない
+    if is_quiet_hours(&settings) {
+        return Ok(());
+    }
+    if let Some(sound) = settings.overlay_sound.as_deref() {
+        play_overlay_sound(sound);
+    }
+    let duration = settings.overlay_duration;
+    let theme = match settings.theme {
+        ThemeSetting::Light => "light".to_string(),
+        ThemeSetting::Dark => "dark".to_string(),
+        ThemeSetting::System => "system".to_string(),
+    };
+    // 一覧と同じ表示形式に揃える
+    let shortcut_key = apply_key_display_style(&shortcut_key, settings.key_display_style);
+    let app_color = find_app_color(&load_keybindings_config(), &app_name);
+
+    // メインウィンドウを非表示
+    if let Some(main_window) = app.get_webview_window("search") {
+        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+        let _ = main_window.hide();
+    }
+
+    // 通知モードではオーバーレイの代わりにOSの通知で表示
+    if settings.overlay_mode == OverlayMode::Notification {
+        active_window::restore_focus_to_last_window();
+        return show_shortcut_notification(&app, &app_name, &action_name, &shortcut_key);
+    }
+
+    // オーバーレイウィンドウを表示（フォーカスは設定しない）
+    if let Some(overlay_window) = app.get_webview_window("keyguide") {
+        // ウィンドウ幅を計算して設定
+        let width = calculate_overlay_width(
+            &app_name,
+            &action_name,
+            &shortcut_key,
+            settings.overlay_width_range(),
+        );
+        let _ = overlay_window.set_size(tauri::Size::Logical(tauri::LogicalSize {
+            width,
+            height: OVERLAY_HEIGHT,
+        }));
+
+        // 保存された位置があればその位置に、なければ中央に表示
+        if let (Some(x), Some(y)) = (settings.overlay_position.x, settings.overlay_position.y) {
+            let _ = overlay_window
+                .set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }));
+        } else {
+            center_on_cursor_monitor(&overlay_window);
+        }
+
+        // フォーカスを奪わずに表示
+        show_window_no_focus(&overlay_window);
+
+        // 元のアプリにフォーカスを戻す
+        active_window::restore_focus_to_last_window();
+
+        // オーバーレイにデータを送信
+        let _ = overlay_window.emit(
+            "overlay-show",
+            OverlayPayload {
+                app_name,
+                action_name,
+                shortcut_key,
+                duration,
+                theme,
+                app_color,
+                fade_ms: OVERLAY_FADE_MS,
+            },
+        );
+
+        // 前のオーバーレイの固定表示は引き継がない
+        set_overlay_pinned(&app, false);
+
+        // Rust側でタイマーを管理（フォーカスがなくてもタイマーが動作するように）
+        // フロントエンドのフェードアウトもこのタイマーから開始し、完了してから非表示にする
+        let generation = OVERLAY_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        let is_current = move || OVERLAY_GENERATION.load(Ordering::SeqCst) == generation;
+        thread::spawn(move || {
+            thread::sleep(Duration::from_secs(u64::from(duration)));
+            // 固定表示中は解除されるまで待機
+            OVERLAY_PINNED.wait_while_pinned();
+            // 待機中に次のオーバーレイが表示された場合はそちらのタイマーに任せる
+            if !is_current() {
+                return;
+            }
+            let _ = overlay_window.emit("overlay-fade-out", ());
+            thread::sleep(Duration::from_millis(
+                OVERLAY_FADE_MS + OVERLAY_HIDE_TAIL_MS,
+            ));
+            if !is_current() {
+                return;
+            }
+            if let Some(overlay) = app.get_webview_window("keyguide") {
+                // Windows API で直接非表示にする（Tauriのhide()が効かない場合の対策）
+                #[cfg(target_os = "windows")]
+                {
+                    if let Ok(hwnd) = overlay.hwnd() {
+                        use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
+                        // SAFETY: ShowWindowは有効なHWND（Tauriから取得）に対して
+                        // SW_HIDEでウィンドウを非表示にする。失敗しても安全。
+                        #[allow(unsafe_code)]
+                        unsafe {
+                            let hwnd = windows::Win32::Foundation::HWND(hwnd.0.cast());
+                            let _ = ShowWindow(hwnd, SW_HIDE);
+                        }
+                    }
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    let _ = overlay.hide();
+                }
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// ショートカットをOSの通知で表示
+/// 通知APIに表示時間の指定がないため、`overlay_duration` ではなくOS側の設定で消える
+fn show_shortcut_notification(
+    app: &AppHandle,
+    app_name: &str,
+    action_name: &str,
+    shortcut_key: &str,
+) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title(format!("{app_name}: {action_name}"))
+        .body(shortcut_key)
+        .show()
+        .map_err(|e| format!("通知を表示できません: {e}"))
+}
+
+// OSのサウンドの場所と拡張子（overlay_sound にサウンド名だけを指定した場合に使用）
+// macOS のシステムサウンドは AIFF 形式で再生できないため対象外
+#[cfg(target_os = "windows")]
+const SYSTEM_SOUND_DIR: Option<(&str, &str)> = Some((r"C:\Windows\Media", "wav"));
+#[cfg(target_os = "linux")]
+const SYSTEM_SOUND_DIR: Option<(&str, &str)> =
+    Some(("/usr/share/sounds/freedesktop/stereo", "oga"));
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+const SYSTEM_SOUND_DIR: Option<(&str, &str)> = None;
+
+/// `overlay_sound` の値から再生するファイルを解決
+/// 存在するファイルパスならそのまま、そうでなければOSのサウンド名として探す
+fn resolve_sound_path(sound: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(sound);
+    if path.is_file() {
+        return Some(path);
+    }
+    let (dir, extension) = SYSTEM_SOUND_DIR?;
+    let builtin = Path::new(dir).join(format!("{sound}.{extension}"));
+    builtin.is_file().then_some(builtin)
+}
+
+/// 効果音ファイルを最後まで再生（再生が終わるまでブロックする）
+fn play_sound_file(path: &Path) -> Result<(), String> {
+    let (_stream, handle) = rodio::OutputStream::try_default()
+        .map_err(|e| format!("音声出力デバイスを開けません: {e}"))?;
+    let file = fs::File::open(path)
+        .map_err(|e| format!("効果音ファイルを開けません: {}: {e}", path.display()))?;
+    let source = rodio::Decoder::new(std::io::BufReader::new(file))
+        .map_err(|e| format!("効果音ファイルを読み込めません: {}: {e}", path.display()))?;
+    let sink = rodio::Sink::try_new(&handle).map_err(|e| format!("効果音を再生できません: {e}"))?;
+    sink.append(source);
+    sink.sleep_until_end();
+    Ok(())
+}
+
+/// オーバーレイの効果音を別スレッドで再生（表示を待たせない、空文字列なら何もしない）
+fn play_overlay_sound(sound: &str) {
+    let sound = sound.trim();
+    if sound.is_empty() {
+        return;
+    }
+    let Some(path) = resolve_sound_path(sound) else {
+        eprintln!("Warning: 効果音が見つかりません: {sound}");
+        return;
+    };
+    thread::spawn(move || {
+        if let Err(e) = play_sound_file(&path) {
+            eprintln!("Warning: {e}");
+        }
+    });
+}
+
+/// メインウィンドウの固定表示状態を変更してトレイとフロントエンドに反映
+fn apply_window_pinned(app: &AppHandle, pinned: bool) -> Result<(), String> {
+    WINDOW_PINNED.store(pinned, Ordering::SeqCst);
+    if let Some(window) = app.get_webview_window("search") {
+        let _ = window.emit("window-pinned", pinned);
+    }
+    refresh_tray_menu(app)
+}
+
+// メインウィンドウが固定表示中かどうかを取得
+#[tauri::command]
+fn get_window_pinned() -> bool {
+    WINDOW_PINNED.load(Ordering::SeqCst)
+}
+
+// メインウィンドウを固定表示（フォーカスを失っても閉じないようにする）
+#[tauri::command]
+fn set_window_pinned(app: AppHandle, pinned: bool) -> Result<(), String> {
+    apply_window_pinned(&app, pinned)
+}
+
+// オーバーレイウィンドウを非表示（固定表示も解除し、次のオーバーレイのタイマーを止めないようにする）
+#[tauri::command]
+fn hide_overlay(app: AppHandle) {
+    set_overlay_pinned(&app, false);
+    if let Some(overlay_window) = app.get_webview_window("keyguide") {
+        let _ = overlay_window.hide();
+    }
+}
+
+/// 固定表示フラグ（解除を待つスレッドは定期的に確認せず、変更時に起こす）
+struct PinFlag {
+    pinned: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl PinFlag {
+    const fn new() -> Self {
+        Self {
+            pinned: Mutex::new(false),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// 固定状態を変更し、解除を待っているスレッドを起こす
+    fn set(&self, pinned: bool) {
+        if let Ok(mut flag) = self.pinned.lock() {
+            *flag = pinned;
+        }
+        self.changed.notify_all();
+    }
+
+    /// 固定表示中かどうか
+    fn is_pinned(&self) -> bool {
+        self.pinned.lock().is_ok_and(|flag| *flag)
+    }
+
+    /// 固定が解除されるまで待機する
+    fn wait_while_pinned(&self) {
+        if let Ok(flag) = self.pinned.lock() {
+            drop(self.changed.wait_while(flag, |pinned| *pinned));
+        }
+    }
+}
+
+/// オーバーレイの固定表示状態を変更してフロントエンドに通知
+fn set_overlay_pinned(app: &AppHandle, pinned: bool) {
+    OVERLAY_PINNED.set(pinned);
+    if let Some(overlay_window) = app.get_webview_window("keyguide") {
+        let _ = overlay_window.emit("overlay-pinned", pinned);
+    }
+}
+
+// オーバーレイを固定表示（タイマーで閉じないようにする）
+#[tauri::command]
+fn pin_overlay(app: AppHandle) {
+    set_overlay_pinned(&app, true);
+}
+
+// オーバーレイの固定表示を解除（タイマーによる非表示を再開）
+#[tauri::command]
+fn unpin_overlay(app: AppHandle) {
+    set_overlay_pinned(&app, false);
+}
+
+// キーバインド設定を生データで取得（設定画面用）
+#[tauri::command]
+fn get_keybindings_raw() -> Vec<AppConfig> {
+    load_user_keybindings_config().to_vec()
+}
+
+// キーバインド設定を保存（設定画面用）
+#[tauri::command]
+fn save_keybindings(config: Vec<AppConfig>) -> Result<(), String> {
+    save_keybindings_config(&config)
+}
+
+/// キーバインド設定ファイルをデフォルトに戻す（戻り値は元の内容を退避したパス）
+fn reset_keybindings_file() -> Result<Option<PathBuf>, String> {
+    let path = get_keybindings_config_path().ok_or("設定ファイルのパスが見つかりません")?;
+    let backup_path = backup_config_file(&path)?;
+    write_keybindings_config(&get_default_keybindings())?;
+    Ok(backup_path)
+}
+
+/// リセット前の設定ファイルを .reset.bak にコピー（ファイルがなければ何もせず None）
+/// 保存のたびに更新される .bak とは別のファイルのため、リセット後に保存しても残る
+fn backup_config_file(path: &Path) -> Result<Option<PathBuf>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let backup_path = with_file_suffix(path, ".reset.bak");
+    fs::copy(path, &backup_path).map_err(|e| format!("バックアップ作成エラー: {e}"))?;
+    Ok(Some(backup_path))
+}
+
+// キーバインド設定をデフォルトに戻す（戻り値は元の設定を退避したパス、ファイルがなかった場合は null）
+#[tauri::command]
+fn reset_keybindings(app: AppHandle) -> Result<Option<String>, String> {
+    let backup_path = reset_keybindings_file()?;
+    let _ = app.emit("keybindings-updated", None::<String>);
+    Ok(backup_path.map(|path| path.display().to_string()))
+}
+
+// アプリ設定をデフォルトに戻す（戻り値は元の設定を退避したパス、ファイルがなかった場合は null）
+#[tauri::command]
+fn reset_settings(app: AppHandle) -> Result<Option<String>, String> {
+    let path = get_settings_path().ok_or("設定ファイルのパスが見つかりません")?;
+    let backup_path = backup_config_file(&path)?;
+
+    let current = load_settings();
+    let settings = AppSettings::default();
+    save_settings(&settings)?;
+
+    // 保存に失敗した場合に登録中のホットキーと設定ファイルが食い違わないよう、保存してから登録し直す
+    unregister_toggle_hotkeys(&app, &current.all_hotkeys());
+    for error in register_toggle_hotkeys(&app, &settings.all_hotkeys()) {
+        eprintln!("Warning: {error}");
+    }
+
+    // OSの状態やバックグラウンドの処理にも反映
+    apply_autostart(&app, settings.autostart)?;
+    MONITOR_ENABLED.store(settings.active_monitor, Ordering::SeqCst);
+    let _ = app.emit("settings-updated", ());
+    let _ = app.emit("primary-label-changed", settings.primary_label);
+    refresh_tray_menu(&app)?;
+
+    Ok(backup_path.map(|path| path.display().to_string()))
+}
+
+// 共有されたキーバインド設定ファイルを取り込むコマンド（戻り値は取り込んだアプリの数）
+// replace が true なら現在の設定を置き換え、false なら同じ名前のアプリを置き換えて新しいアプリを追加
+// 取り込んだ内容の警告は validate_keybindings で確認する
+#[tauri::command]
+fn import_keybindings(app: AppHandle, path: String, replace: bool) -> Result<usize, String> {
+    let path = PathBuf::from(path);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("{} の読み込みに失敗しました: {e}", path.display()))?;
+    let imported = parse_keybindings_str(&path, &content)
+        .map_err(|e| format!("{} のパースに失敗しました: {e}", path.display()))?;
+    let count = imported.len();
+
+    let config =
+        apply_imported_keybindings(load_user_keybindings_config().to_vec(), imported, replace)?;
+    save_keybindings_config(&config)?;
+    // 特定のアプリに限らない更新のためアプリ名は null
+    let _ = app.emit("keybindings-updated", None::<String>);
+    Ok(count)
+}
+
+/// 取り込んだアプリ設定を現在の設定に反映した結果を返す
+/// 置き換えの場合、空の設定ですべてのアプリが消えないよう拒否する
+fn apply_imported_keybindings(
+    current: Vec<AppConfig>,
+    imported: Vec<AppConfig>,
+    replace: bool,
+) -> Result<Vec<AppConfig>, String> {
+    if !replace {
+        return Ok(merge_app_configs(current, imported));
+    }
+    if imported.is_empty() {
+        return Err("取り込むファイルにアプリが含まれていないため、置き換えできません".to_string());
+    }
+    Ok(imported)
+}
+
+/// 指定したアプリのキーバインドを編集して保存し、"keybindings-updated" イベントで通知
+fn edit_app_keybindings(
+    app: &AppHandle,
+    app_name: &str,
+    edit: impl FnOnce(&mut Vec<Keybinding>) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut config = load_user_keybindings_config().to_vec();
+    // デフォルト設定のみにあるアプリは、ユーザー設定に複製してから編集
+    // （keybindings.d/ のアプリは編集対象外）
+    if load_settings().merge_defaults && !config.iter().any(|config| config.get_name() == app_name)
+    {
+        if let Some(default) = get_default_keybindings()
+            .into_iter()
+            .find(|config| config.get_name() == app_name)
+        {
+            config.push(default);
+        }
+    }
+    let target = config
+        .iter_mut()
+        .find(|config| config.get_name() == app_name)
+        .ok_or_else(|| format!("アプリが見つかりません: {app_name}"))?;
+    edit(&mut target.keybindings)?;
+    save_keybindings_config(&config)?;
+    let _ = app.emit("keybindings-updated", app_name);
+    Ok(())
+}
+
+/// キーバインドの番号が範囲内か確認
+fn check_keybinding_index(keybindings: &[Keybinding], index: usize) -> Result<(), String> {
+    if index < keybindings.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "キーバインドの番号が範囲外です: {index}（{}件）",
+            keybindings.len()
+        ))
+    }
+}
+
+// アプリにキーバインドを追加するコマンド
+#[tauri::command]
+fn add_keybinding(app_handle: AppHandle, app: String, kb: Keybinding) -> Result<(), String> {
+    edit_app_keybindings(&app_handle, &app, |keybindings| {
+        keybindings.push(kb);
+        Ok(())
+    })
+}
+
+// アプリのキーバインドを更新するコマンド（index はアプリ内での番号）
+#[tauri::command]
+fn update_keybinding(
+    app_handle: AppHandle,
+    app: String,
+    index: usize,
+    kb: Keybinding,
+) -> Result<(), String> {
+    edit_app_keybindings(&app_handle, &app, |keybindings| {
+        check_keybinding_index(keybindings, index)?;
+        keybindings[index] = kb;
+        Ok(())
+    })
+}
+
+// アプリのキーバインドを削除するコマンド（index はアプリ内での番号）
+#[tauri::command]
+fn delete_keybinding(app_handle: AppHandle, app: String, index: usize) -> Result<(), String> {
+    edit_app_keybindings(&app_handle, &app, |keybindings| {
+        check_keybinding_index(keybindings, index)?;
+        keybindings.remove(index);
+        Ok(())
+    })
+}
+
+// keybindings.json の内容をそのまま取得（外部の同期ツール用、パース・正規化しない）
+#[tauri::command]
+fn read_config_raw() -> Result<String, String> {
+    let path = get_keybindings_config_path().ok_or("設定ディレクトリが見つかりません")?;
+    fs::read_to_string(&path).map_err(|e| format!("ファイル読み込みエラー: {e}"))
+}
+
+// keybindings.json に内容をそのまま書き込む（パースできることを確認し、書式は保持）
+#[tauri::command]
+fn write_config_raw(content: String) -> Result<(), String> {
+    let path = get_keybindings_config_path().ok_or("設定ディレクトリが見つかりません")?;
+    parse_config_str::<serde_json::Value>(&path, &content)
+        .and_then(migrate_config)
+        .map_err(|e| format!("パースエラー: {e}"))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("ディレクトリ作成エラー: {e}"))?;
+    }
+    write_with_backup(&path, &content)?;
+
+    // キャッシュをクリア（次回読み込み時に再取得）
+    *write_cache(&KEYBINDINGS_CACHE) = None;
+
+    Ok(())
+}
+
+// キーバインド設定ウィンドウを開く
+#[tauri::command]
+fn open_keybindings_window(app: AppHandle) -> Result<(), String> {
+    app.get_webview_window("keybindings").map_or_else(
+        || Err("キーバインド設定ウィンドウが見つかりません".to_string()),
+        |window| {
+            // ウィンドウを中央に配置して表示
+            let _ = window.center();
+            let _ = window.show();
+            let _ = window.set_focus();
+            Ok(())
+        },
+    )
+}
+
+// キーバインド設定ウィンドウを閉じる（非表示にする）
+#[tauri::command]
+fn close_keybindings_window(app: AppHandle) {
+    if let Some(window) = app.get_webview_window("keybindings") {
+        let _ = window.hide();
+    }
+}
+
+// アプリのバージョンを取得
+#[tauri::command]
+fn get_app_version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+// ショートカットキーをクリップボードにコピー
+#[tauri::command]
+fn copy_shortcut(app: AppHandle, text: String) -> Result<(), String> {
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("クリップボードへの書き込みに失敗しました: {e}"))
+}
+
+// バージョン情報ウィンドウを開く
+#[tauri::command]
+fn open_about_window(app: AppHandle) -> Result<(), String> {
+    app.get_webview_window("about").map_or_else(
+        || Err("バージョン情報ウィンドウが見つかりません".to_string()),
+        |window| {
+            let _ = window.center();
+            let _ = window.show();
+            let _ = window.set_focus();
+            Ok(())
+        },
+    )
+}
+
+// バージョン情報ウィンドウを閉じる（非表示にする）
+#[tauri::command]
+fn close_about_window(app: AppHandle) {
+    if let Some(window) = app.get_webview_window("about") {
+        let _ = window.hide();
+    }
+}
+
+// オーバーレイの位置を保存
+#[tauri::command]
+fn save_overlay_position(x: i32, y: i32) -> Result<(), String> {
+    let mut settings = load_settings();
+    settings.overlay_position = OverlayPosition {
+        x: Some(x),
+        y: Some(y),
+    };
+    save_settings(&settings)
+}
+
+/// OSのログイン項目への登録状態を変更（Windows: Runレジストリ、macOS: `LaunchAgent`）
+fn apply_autostart(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| format!("自動起動の設定に失敗しました: {e}"))
+}
+
+// ログイン時の自動起動を設定するコマンド
+#[tauri::command]
+fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
+    apply_autostart(&app, enabled)?;
+    let mut settings = load_settings();
+    settings.autostart = enabled;
+    save_settings(&settings)
+}
+
+// アクティブウィンドウの監視を有効/無効にするコマンド（設定にも保存）
+#[tauri::command]
+fn set_monitor_enabled(enabled: bool) -> Result<(), String> {
+    MONITOR_ENABLED.store(enabled, Ordering::SeqCst);
+    let mut settings = load_settings();
+    settings.active_monitor = enabled;
+    save_settings(&settings)
+}
+
+// ログイン時の自動起動が有効かどうかを取得するコマンド
+#[tauri::command]
+fn get_autostart(app: AppHandle) -> bool {
+    app.autolaunch().is_enabled().unwrap_or(false)
+}
+
+// settings.json のキャッシュがファイルと一致しているか確認するコマンド
+// 更新時刻が食い違っていればキャッシュを破棄して読み込み直し、false を返す
+#[tauri::command]
+fn verify_settings_cache() -> bool {
+    let Some(path) = get_settings_path() else {
+        return true;
+    };
+    let is_consistent = {
+        let mut cache_guard = write_cache(&SETTINGS_CACHE);
+        let is_stale = cache_guard
+            .as_ref()
+            .is_some_and(|cache| cache.last_modified != get_file_modified_time(&path));
+        if is_stale {
+            *cache_guard = None;
+        }
+        !is_stale
+    };
+    if !is_consistent {
+        load_settings();
+    }
+    is_consistent
+}
+
+// 設定ファイルのエラー一覧を取得するコマンド（settings.json のエラーは strict_config 有効時のみ記録される）
+#[tauri::command]
+fn get_config_errors() -> Vec<String> {
+    // 最新の状態で判定するため先に読み込む
+    load_settings();
+    load_keybindings_config();
+    [&SETTINGS_CONFIG_ERROR, &KEYBINDINGS_CONFIG_ERROR]
+        .iter()
+        .filter_map(|slot| slot.lock().ok().and_then(|guard| guard.clone()))
+        .collect()
+}
+
+/// 重複したアプリ名と、同じアプリ内で重複したアクション名を検出
+/// `title_contains` が異なるものはウィンドウごとの定義のため重複とみなさない
+fn find_duplicates(apps: &[AppConfig]) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    let mut seen_apps = Vec::new();
+    for app in apps {
+        let app_name = app.get_name();
+        if seen_apps.contains(&app_name) {
+            warnings.push(ConfigWarning::DuplicateApp {
+                app: app_name.clone(),
+            });
+        } else {
+            seen_apps.push(app_name.clone());
+        }
+
+        let mut seen_actions = Vec::new();
+        for kb in &app.keybindings {
+            let id = (&kb.action, &kb.title_contains);
+            if seen_actions.contains(&id) {
+                warnings.push(ConfigWarning::DuplicateAction {
+                    app: app_name.clone(),
+                    action: kb.action.clone(),
+                });
+            } else {
+                seen_actions.push(id);
+            }
+        }
+    }
+    warnings
+}
+
+/// 比較用にキー文字列を正規化（修飾キーの表記ゆれ・順序と大文字小文字を無視）
+fn canonical_key(key: &str) -> String {
+    key.split("->")
+        .map(|step| {
+            let step = step.trim();
+            split_key_combo(step).map_or_else(
+                || step.to_lowercase(),
+                |(modifiers, main_key)| {
+                    modifiers
+                        .into_iter()
+                        .map(str::to_string)
+                        .chain(std::iter::once(main_key.to_lowercase()))
+                        .collect::<Vec<_>>()
+                        .join("+")
+                },
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// 同じアプリ内で同じキーに割り当てられた複数のアクションを検出
+/// キーが空または "-"（未割り当て）のものと、`title_contains` が異なるものは対象外
+/// OS別のキーがあるため、アプリの対象OS（`os` 指定がなければ全OS）ごとに確認する
+fn find_key_conflicts(apps: &[AppConfig]) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    for app in apps {
+        let target_os = app
+            .os
+            .as_ref()
+            .map_or(&OsType::ALL[..], std::slice::from_ref);
+        for os in target_os {
+            // (正規化したキー, 先頭のキーの表記, キーバインド)
+            let mut groups: Vec<(String, String, Vec<&Keybinding>)> = Vec::new();
+            for kb in &app.keybindings {
+                let Some(raw_key) = kb.get_key(os).filter(|key| !key.trim().is_empty()) else {
+                    continue;
+                };
+                let key = canonical_key(&raw_key);
+                let group = groups.iter_mut().find(|(group_key, _, members)| {
+                    *group_key == key && members[0].title_contains == kb.title_contains
+                });
+                match group {
+                    Some((_, _, members)) => members.push(kb),
+                    None => groups.push((key, raw_key, vec![kb])),
+                }
+            }
+            let conflicts = groups
+                .into_iter()
+                .filter(|(_, _, members)| members.len() > 1)
+                .map(|(_, raw_key, members)| ConfigWarning::KeyConflict {
+                    app: app.get_name(),
+                    key: raw_key,
+                    actions: members.iter().map(|kb| kb.action.clone()).collect(),
+                });
+            // 全OS共通のキーは各OSで同じ重複が見つかるため1つにまとめる
+            for conflict in conflicts {
+                if !warnings.contains(&conflict) {
+                    warnings.push(conflict);
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// `os` を指定したアプリで、OS別のキーにそのOSのキーがないキーバインドを検出
+/// （そのOSでは常に未割り当てとなるため、OS名の誤りや指定漏れの可能性が高い）
+fn find_missing_os_keys(apps: &[AppConfig]) -> Vec<ConfigWarning> {
+    apps.iter()
+        .filter_map(|app| Some((app, app.os.as_ref()?)))
+        .flat_map(|(app, os)| {
+            app.keybindings
+                .iter()
+                .filter(|kb| {
+                    matches!(kb.key, KeyValue::PerOs { .. }) && kb.key.steps_for(os).is_none()
+                })
+                .map(|kb| ConfigWarning::MissingOsKey {
+                    app: app.get_name(),
+                    action: kb.action.clone(),
+                    os: os.clone(),
+                })
+        })
+        .collect()
+}
+
+/// 重複したアプリ名・アクション名と、キーの重複・OS別のキーの指定漏れを検出
+fn find_config_warnings(apps: &[AppConfig]) -> Vec<ConfigWarning> {
+    let mut warnings = find_duplicates(apps);
+    warnings.extend(find_key_conflicts(apps));
+    warnings.extend(find_missing_os_keys(apps));
+    warnings
+}
+
+// keybindings.json を検証するコマンド（パースエラーを行・列付きで返す）
+// 読み込み時はエラーがあるとデフォルトに戻るため、原因を画面に表示するために使う
+// パースできた場合は重複したアプリ名・アクション名と、キーの重複・OS別のキーの指定漏れを警告として返す
+#[tauri::command]
+fn validate_keybindings() -> Result<Vec<ConfigWarning>, Vec<String>> {
+    let path = get_keybindings_config_path()
+        .ok_or_else(|| vec!["設定ディレクトリが見つかりません".to_string()])?;
+    // ファイルがなければデフォルト設定が使われるため問題なし
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| vec![format!("{} の読み込みに失敗しました: {e}", path.display())])?;
+    // 構文エラーは行・列付きで報告し、形式の誤りはバージョン移行後の内容で確認
+    let parsed = if is_yaml_path(&path) {
+        serde_yaml_ng::from_str::<serde_json::Value>(&content)
+            .map_err(|e| (e.to_string(), e.location().map(|l| (l.line(), l.column()))))
+    } else {
+        parse_json_relaxed::<serde_json::Value>(&content)
+            .map_err(|e| (e.to_string(), Some((e.line(), e.column()))))
+    };
+    let (message, position) = match parsed {
+        Ok(value) => {
+            return migrate_config(value)
+                .map(|config| find_config_warnings(&config))
+                .map_err(|e| vec![e]);
+        }
+        Err(error) => error,
+    };
+    // エラーメッセージ末尾の " at line X column Y" は行・列として別に表示
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(head, _)| head);
+    Err(vec![position.map_or_else(
+        || message.to_string(),
+        |(line, column)| format!("{line}行目 {column}列目: {message}"),
+    )])
+}
+
+// 共有用の設定から除外する、環境ごとの設定項目
+const MACHINE_SPECIFIC_SETTINGS: [&str; 3] = ["overlay_position", "window_bounds", "autostart"];
+
+/// 設定のJSONから環境ごとの設定項目を取り除く
+fn remove_machine_specific_settings(value: &mut serde_json::Value) {
+    if let Some(map) = value.as_object_mut() {
+        for key in MACHINE_SPECIFIC_SETTINGS {
+            map.remove(key);
+        }
+    }
+}
+
+// 現在の設定を共有用のJSONとして取得するコマンド（位置や自動起動などの環境ごとの設定は除外）
+#[tauri::command]
+fn export_settings() -> String {
+    let mut value = serde_json::to_value(load_settings()).unwrap_or_default();
+    remove_machine_specific_settings(&mut value);
+    serde_json::to_string_pretty(&value).unwrap_or_default()
+}
+
+// 共有された設定のJSONを読み込んで適用するコマンド（環境ごとの設定は現在の値を維持）
+#[tauri::command]
+fn import_settings(app: AppHandle, json: String) -> Result<(), String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| format!("JSONパースエラー: {e}"))?;
+    remove_machine_specific_settings(&mut value);
+    let imported: AppSettings =
+        serde_json::from_value(value).map_err(|e| format!("設定の形式が正しくありません: {e}"))?;
+
+    if let Some(invalid) = std::iter::once(&imported.hotkey)
+        .chain(&imported.hotkeys)
+        .find(|hotkey| parse_hotkey(hotkey).is_none())
+    {
+        return Err(format!("ホットキーの形式が正しくありません: {invalid}"));
+    }
+    let (min_width, max_width) = (
+        imported.overlay_min_width.unwrap_or(OVERLAY_MIN_WIDTH),
+        imported.overlay_max_width.unwrap_or(OVERLAY_MAX_WIDTH),
+    );
+    validate_overlay_width_range(min_width, max_width)?;
+
+    let current = load_settings();
+    let current_hotkeys = current.all_hotkeys();
+    let settings = AppSettings {
+        overlay_position: current.overlay_position,
+        window_bounds: current.window_bounds,
+        autostart: current.autostart,
+        ..imported
+    };
+    save_settings(&settings)?;
+    MONITOR_ENABLED.store(settings.active_monitor, Ordering::SeqCst);
+
+    // 保存に失敗した場合に登録中のホットキーと設定ファイルが食い違わないよう、保存してから登録し直す
+    unregister_toggle_hotkeys(&app, &current_hotkeys);
+    for error in register_toggle_hotkeys(&app, &settings.all_hotkeys()) {
+        eprintln!("Warning: {error}");
+    }
+
+    let _ = app.emit("primary-label-changed", settings.primary_label);
+    refresh_tray_menu(&app)
+}
+
+// 指定した設定項目をデフォルト値に戻す
+#[tauri::command]
+fn reset_setting(app: AppHandle, key: String) -> Result<(), String> {
+    let defaults = AppSettings::default();
+    // OSや実行中の処理にも反映が必要な項目
+    match key.as_str() {
+        "hotkey" => return set_hotkey(app, defaults.hotkey),
+        "hotkeys" => return set_hotkeys(app, defaults.hotkeys).map(|_| ()),
+        "active_monitor" => MONITOR_ENABLED.store(defaults.active_monitor, Ordering::SeqCst),
+        "autostart" => apply_autostart(&app, defaults.autostart)?,
+        _ => {}
+    }
+    let mut settings = load_settings();
+    reset_setting_field(&mut settings, &key)?;
+    save_settings(&settings)?;
+
+    // 言語を戻した場合はトレイメニューにも反映
+    if key == "language" {
+        refresh_tray_menu(&app)?;
+    }
+    Ok(())
+}
+
+/// 設定の指定した項目をデフォルト値に戻す（不明な項目はエラー）
+fn reset_setting_field(settings: &mut AppSettings, key: &str) -> Result<(), String> {
+    let defaults = AppSettings::default();
+    match key {
+        "theme" => settings.theme = defaults.theme,
+        "hotkey" => settings.hotkey = defaults.hotkey,
+        "hotkeys" => settings.hotkeys = defaults.hotkeys,
+        "overlay_duration" => settings.overlay_duration = defaults.overlay_duration,
+        "overlay_position" => settings.overlay_position = defaults.overlay_position,
+        "strict_config" => settings.strict_config = defaults.strict_config,
+        "window_bounds" => settings.window_bounds = defaults.window_bounds,
+        "always_center" => settings.always_center = defaults.always_center,
+        "language" => settings.language = defaults.language,
+        "primary_label" => settings.primary_label = defaults.primary_label,
+        "require_tags" => settings.require_tags = defaults.require_tags,
+        "overlay_min_width" => settings.overlay_min_width = defaults.overlay_min_width,
+        "overlay_max_width" => settings.overlay_max_width = defaults.overlay_max_width,
+        "key_display_style" => settings.key_display_style = defaults.key_display_style,
+        "match_owner_window" => settings.match_owner_window = defaults.match_owner_window,
+        "escape_to_hide" => settings.escape_to_hide = defaults.escape_to_hide,
+        "overlay_mode" => settings.overlay_mode = defaults.overlay_mode,
+        "overlay_sound" => settings.overlay_sound = defaults.overlay_sound,
+        "default_shortcut_sort" => settings.default_shortcut_sort = defaults.default_shortcut_sort,
+        "merge_defaults" => settings.merge_defaults = defaults.merge_defaults,
+        "quiet_hours_start" => settings.quiet_hours_start = defaults.quiet_hours_start,
+        "quiet_hours_end" => settings.quiet_hours_end = defaults.quiet_hours_end,
+        "active_monitor" => settings.active_monitor = defaults.active_monitor,
+        "autostart" => settings.autostart = defaults.autostart,
+        _ => return Err(format!("不明な設定項目です: {key}")),
+    }
+    Ok(())
+}
+
+/// メインウィンドウ切り替え用のグローバルホットキーを登録
+/// 形式が不正な場合と、他のアプリが使用中などで登録できない場合を区別したエラーを返す
+fn register_toggle_hotkey(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+    let shortcut = parse_hotkey(hotkey)
+        .ok_or_else(|| format!("ホットキーの形式が正しくありません: {hotkey}"))?;
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                toggle_window(app);
+            }
+        })
+        .map_err(|e| format!("ホットキーを登録できません（他のアプリで使用中の可能性があります）: {hotkey} ({e})"))
+}
+
+/// 複数の起動ホットキーを登録（登録できなかったものはホットキーごとのエラーを返す）
+fn register_toggle_hotkeys(app: &AppHandle, hotkeys: &[String]) -> Vec<String> {
+    let errors: Vec<String> = hotkeys
+        .iter()
+        .filter_map(|hotkey| register_toggle_hotkey(app, hotkey).err())
+        .collect();
+    if let Ok(mut hotkey_errors) = HOTKEY_ERRORS.lock() {
+        hotkey_errors.clone_from(&errors);
+    }
+    errors
+}
+
+/// 起動ホットキーをすべて解除
+fn unregister_toggle_hotkeys(app: &AppHandle, hotkeys: &[String]) {
+    for shortcut in hotkeys.iter().filter_map(|hotkey| parse_hotkey(hotkey)) {
+        let _ = app.global_shortcut().unregister(shortcut);
+    }
+}
+
+// 登録できなかった起動ホットキーのエラー一覧を取得するコマンド
+#[tauri::command]
+fn get_hotkey_errors() -> Vec<String> {
+    HOTKEY_ERRORS
+        .lock()
+        .map(|errors| errors.clone())
+        .unwrap_or_default()
+}
+
+// 起動ホットキーを表示用の形式で取得するコマンド（ショートカット一覧と同じ表示形式）
+#[tauri::command]
+fn get_display_hotkey() -> String {
+    display_hotkey(&load_settings(), &OsType::current())
+}
+
+/// 起動ホットキーを指定したOSの表示形式に変換
+fn display_hotkey(settings: &AppSettings, os: &OsType) -> String {
+    let hotkey = normalize_key_for_display(&settings.hotkey);
+    apply_key_display_style_for(&hotkey, settings.key_display_style, os)
+}
+
+// 同じキーに設定された起動ホットキーの一覧を取得するコマンド（重複分は登録されないため）
+#[tauri::command]
+fn get_hotkey_collisions() -> Vec<String> {
+    load_settings().hotkey_collisions()
+}
+
+// ホットキーが登録可能かを確認するコマンド（設定画面で保存前に確認する用）
+#[tauri::command]
+fn check_hotkey_available(app: AppHandle, hotkey: String) -> bool {
+    let Some(shortcut) = parse_hotkey(&hotkey) else {
+        return false;
+    };
+    let global_shortcut = app.global_shortcut();
+    // 自分が登録済みのホットキーはそのまま使用可能
+    if global_shortcut.is_registered(shortcut) {
+        return true;
+    }
+    // 実際に登録を試して確認し、成功したらすぐに解除
+    let available = global_shortcut.on_shortcut(shortcut, |_, _, _| {}).is_ok();
+    if available {
+        let _ = global_shortcut.unregister(shortcut);
+    }
+    available
+}
+
+// ホットキーを変更するコマンド（登録できた場合のみ保存）
+#[tauri::command]
+fn set_hotkey(app: AppHandle, hotkey: String) -> Result<(), String> {
+    let mut settings = load_settings();
+    let new_shortcut = parse_hotkey(&hotkey)
+        .ok_or_else(|| format!("ホットキーの形式が正しくありません: {hotkey}"))?;
+
+    let changed = parse_hotkey(&settings.hotkey) != Some(new_shortcut);
+    if changed {
+        let old_hotkeys = settings.all_hotkeys();
+        unregister_toggle_hotkeys(&app, &old_hotkeys);
+        if let Err(e) = register_toggle_hotkey(&app, &hotkey) {
+            // 登録に失敗したら元のホットキーに戻す
+            register_toggle_hotkeys(&app, &old_hotkeys);
+            return Err(e);
+        }
+    }
+
+    settings.hotkey = hotkey;
+    if changed {
+        // 先頭（hotkey）は登録済みなので、追加分のみ登録
+        for error in register_toggle_hotkeys(&app, &settings.all_hotkeys()[1..]) {
+            eprintln!("Warning: {error}");
+        }
+    }
+    save_settings(&settings)
+}
+
+// 追加の起動ホットキーを変更するコマンド（戻り値は登録できなかったホットキーのエラー）
+#[tauri::command]
+fn set_hotkeys(app: AppHandle, hotkeys: Vec<String>) -> Result<Vec<String>, String> {
+    if let Some(invalid) = hotkeys.iter().find(|hotkey| parse_hotkey(hotkey).is_none()) {
+        return Err(format!("ホットキーの形式が正しくありません: {invalid}"));
+    }
+
+    let mut settings = load_settings();
+    unregister_toggle_hotkeys(&app, &settings.all_hotkeys());
+    settings.hotkeys = hotkeys;
+    let errors = register_toggle_hotkeys(&app, &settings.all_hotkeys());
+    save_settings(&settings)?;
+    Ok(errors)
+}
+
+/// ホットキー文字列をパースしてShortcut構造体に変換
+#[allow(clippy::cognitive_complexity)] // キーコードマッピングのため複雑になるが明確な構造
+fn parse_hotkey(hotkey: &str) -> Option<Shortcut> {
+    let normalized = normalize_hotkey_for_tauri(hotkey);
+    let parts: Vec<&str> = normalized.split('+').collect();
+
+    if parts.is_empty() {
+        return None;
+    }
+
+    let mut modifiers = Modifiers::empty();
+    let mut key_code: Option<Code> = None;
+
+    for part in parts {
+        let part_lower = part.to_lowercase();
+        match part_lower.as_str() {
+            "ctrl" | "control" => modifiers |= Modifiers::CONTROL,
+            "shift" => modifiers |= Modifiers::SHIFT,
+            "alt" | "option" => modifiers |= Modifiers::ALT,
+            "cmd" | "command" | "super" | "win" | "meta" => modifiers |= Modifiers::META,
+            _ => {
+                // キーコードを解析
+                key_code = match part_lower.as_str() {
+                    "a" => Some(Code::KeyA),
+                    "b" => Some(Code::KeyB),
+                    "c" => Some(Code::KeyC),
+                    "d" => Some(Code::KeyD),
+                    "e" => Some(Code::KeyE),
+                    "f" => Some(Code::KeyF),
+                    "g" => Some(Code::KeyG),
+                    "h" => Some(Code::KeyH),
+                    "i" => Some(Code::KeyI),
+                    "j" => Some(Code::KeyJ),
+                    "k" => Some(Code::KeyK),
+                    "l" => Some(Code::KeyL),
+                    "m" => Some(Code::KeyM),
+                    "n" => Some(Code::KeyN),
+                    "o" => Some(Code::KeyO),
+                    "p" => Some(Code::KeyP),
+                    "q" => Some(Code::KeyQ),
+                    "r" => Some(Code::KeyR),
+                    "s" => Some(Code::KeyS),
+                    "t" => Some(Code::KeyT),
+                    "u" => Some(Code::KeyU),
+                    "v" => Some(Code::KeyV),
+                    "w" => Some(Code::KeyW),
+                    "x" => Some(Code::KeyX),
+                    "y" => Some(Code::KeyY),
+                    "z" => Some(Code::KeyZ),
+                    "0" => Some(Code::Digit0),
+                    "1" => Some(Code::Digit1),
+                    "2" => Some(Code::Digit2),
+                    "3" => Some(Code::Digit3),
+                    "4" => Some(Code::Digit4),
+                    "5" => Some(Code::Digit5),
+                    "6" => Some(Code::Digit6),
+                    "7" => Some(Code::Digit7),
+                    "8" => Some(Code::Digit8),
+                    "9" => Some(Code::Digit9),
+                    "space" => Some(Code::Space),
+                    "enter" | "return" => Some(Code::Enter),
+                    "escape" | "esc" => Some(Code::Escape),
+                    "tab" => Some(Code::Tab),
+                    "backspace" => Some(Code::Backspace),
+                    "delete" => Some(Code::Delete),
+                    "up" => Some(Code::ArrowUp),
+                    "down" => Some(Code::ArrowDown),
+                    "left" => Some(Code::ArrowLeft),
+                    "right" => Some(Code::ArrowRight),
+                    "f1" => Some(Code::F1),
+                    "f2" => Some(Code::F2),
+                    "f3" => Some(Code::F3),
+                    "f4" => Some(Code::F4),
+                    "f5" => Some(Code::F5),
+                    "f6" => Some(Code::F6),
+                    "f7" => Some(Code::F7),
+                    "f8" => Some(Code::F8),
+                    "f9" => Some(Code::F9),
+                    "f10" => Some(Code::F10),
+                    "f11" => Some(Code::F11),
+                    "f12" => Some(Code::F12),
+                    _ => None,
+                };
+            }
+        }
+    }
+
+    key_code.map(|code| Shortcut::new(Some(modifiers), code))
+}
+
+/// キーバインド設定ファイルを開く（なければデフォルト設定で作成してから開く）
+fn open_or_create_config_file() -> Result<(), String> {
+    let path = get_keybindings_config_path().ok_or("設定ファイルのパスが見つかりません")?;
+    if !path.exists() {
+        write_keybindings_config(&get_default_keybindings())?;
+    }
+    open_file_with_default_app(&path)
+}
+
+/// コマンドライン引数による操作を実行（GUIは起動しない）
+/// 対象の引数がなければ None を返し、通常どおり起動する
+fn run_cli_command(args: &[String]) -> Option<Result<(), String>> {
+    let command = args.iter().find(|arg| {
+        matches!(
+            arg.as_str(),
+            "--open-config" | "--reset-config" | "--print-config-path"
+        )
+    })?;
+
+    // リリースビルドの Windows はコンソールを持たないため、起動元のコンソールに出力する
+    #[cfg(all(target_os = "windows", not(debug_assertions)))]
+    {
+        use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        // SAFETY: AttachConsoleは引数に親プロセスを示す定数を渡すだけで、
+        // 失敗しても（コンソールから起動されていない場合など）出力されないだけで安全。
+        #[allow(unsafe_code)]
+        unsafe {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+
+    let result = match command.as_str() {
+        "--open-config" => open_or_create_config_file(),
+        "--reset-config" => reset_keybindings_file().map(|backup_path| {
+            println!("キーバインド設定をデフォルトに戻しました");
+            if let Some(backup_path) = backup_path {
+                println!("元の設定: {}", backup_path.display());
+            }
+        }),
+        _ => get_keybindings_config_path()
+            .ok_or_else(|| "設定ファイルのパスが見つかりません".to_string())
+            .map(|path| println!("{}", path.display())),
+    };
+    Some(result)
+}
+
+#[allow(clippy::too_many_lines)] // Tauri 2.0のセットアップパターンに従う
+fn main() {
+    // コマンドライン引数による操作（完了したらGUIを起動せずに終了）
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = run_cli_command(&args) {
+        if let Err(e) = result {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            // 2つ目のインスタンスは起動せずに終了し、既存のウィンドウを表示する
+            // （トレイやホットキーの二重登録を防ぐ）
+            if let Some(window) = app.get_webview_window("search") {
+                // コマンドライン引数は既存のインスタンスに渡す
+                // （window-shown より先に送り、表示時の検索語として使われるようにする）
+                let _ = window.emit(
+                    "second-instance",
+                    SecondInstancePayload {
+                        args: argv.into_iter().skip(1).collect(),
+                        cwd,
+                    },
+                );
+                show_main_window(&window);
+            }
+        }))
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_autostart::init(
+            MacosLauncher::LaunchAgent,
+            None,
+        ))
+        .setup(|app| {
+            let app_handle = app.handle().clone();
+            let _ = APP_HANDLE.set(app_handle.clone());
+
+            // 最初のリクエストを即座に返せるよう、キャッシュを事前に読み込む
+            warm_caches();
+
+            // システムトレイを設定
+            let menu = build_tray_menu(&app_handle)?;
+
+            let app_handle_for_tray = app_handle.clone();
+            TrayIconBuilder::with_id(TRAY_ID)
+                .icon(app.default_window_icon().unwrap().clone())
+                .icon_as_template(true)
+                .tooltip(format_tray_tooltip(
+                    None,
+                    load_settings().language.resolve(),
+                ))
+                .menu(&menu)
+                .on_tray_icon_event(move |_tray, event| match event {
+                    TrayIconEvent::Click {
+                        button: MouseButton::Left,
+                        button_state: MouseButtonState::Up,
+                        ..
+                    } => {
+                        toggle_window(&app_handle_for_tray);
+                    }
+                    // メニューを開く前に検出中のアプリを最新化
+                    TrayIconEvent::Enter { .. } => {
+                        let _ = refresh_tray_matched_apps(&app_handle_for_tray);
+                    }
+                    _ => {}
+                })
+                .on_menu_event(move |app, event| match event.id.as_ref() {
+                    "show" => {
+                        toggle_window(app);
+                    }
+                    "pin" => {
+                        let pinned = !WINDOW_PINNED.load(Ordering::SeqCst);
+                        if let Err(e) = apply_window_pinned(app, pinned) {
+                            eprintln!("Warning: {e}");
+                        }
+                    }
+                    "keybindings" => {
+                        if let Some(window) = app.get_webview_window("keybindings") {
+                            let _ = window.center();
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                    }
+                    "config" => {
+                        let _ = open_config_file();
+                    }
+                    "update" => {
+                        if let Some(window) = app.get_webview_window("search") {
+                            let _ = window.emit("check-update", ());
+                        }
+                    }
+                    "about" => {
+                        if let Some(window) = app.get_webview_window("about") {
+                            let _ = window.center();
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                    }
+                    "quit" => {
+                        // 監視スレッドを止めてから終了
+                        stop_active_window_monitor();
+                        // Tauri 2.0: AppHandle::exit() でアプリケーションを正常終了
+                        app.exit(0);
+                    }
+                    // 検出中のアプリを選択したらそのアプリのショートカットに絞り込んで表示
+                    id => {
+                        let (Some(app_name), Some(window)) = (
+                            id.strip_prefix(MATCHED_APP_MENU_PREFIX),
+                            app.get_webview_window("search"),
+                        ) else {
+                            return;
+                        };
+                        if !window.is_visible().unwrap_or(false) {
+                            show_main_window(&window);
+                        }
+                        let _ = window.emit("filter-app", app_name);
+                    }
+                })
+                .build(app)?;
+
+            // バックグラウンドでアクティブウィンドウを監視開始（設定で無効なら待機のみ）
+            MONITOR_ENABLED.store(load_settings().active_monitor, Ordering::SeqCst);
+            start_active_window_monitor(app_handle.clone());
+
+            // 設定からホットキーを読み込み
+            let settings = load_settings();
+
+            // 重複した起動ホットキーは1つだけ登録されるため警告
+            for collision in settings.hotkey_collisions() {
+                eprintln!("Warning: 起動ホットキーが重複しています: {collision}");
+            }
+
+            // グローバルホットキーを登録（失敗したものは個別に警告）
+            for error in register_toggle_hotkeys(&app_handle, &settings.all_hotkeys()) {
+                eprintln!("Warning: {error}");
+            }
+
+            // 設定ファイルの自動起動設定をOSの登録状態に反映
+            if app.autolaunch().is_enabled().ok() != Some(settings.autostart) {
+                if let Err(e) = apply_autostart(&app_handle, settings.autostart) {
+                    eprintln!("Warning: Failed to apply autostart setting: {e}");
+                }
+            }
+
+            // 初期表示
+            if let Some(window) = app.get_webview_window("search") {
+                WINDOW_VISIBLE.store(true, Ordering::SeqCst);
+                position_main_window(&window);
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            // 検索ウィンドウのみ処理（キーガイドウィンドウは除外）
+            if window.label() != "search" {
+                return;
+            }
+
+            match event {
+                // フォーカスを失ったらウィンドウを非表示（固定表示中は除く）
+                WindowEvent::Focused(focused) => {
+                    if !focused && !WINDOW_PINNED.load(Ordering::SeqCst) {
+                        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+                        let _ = window.hide();
+                    }
+                }
+                // 閉じるボタンでアプリを終了せず、ウィンドウを非表示にする（固定表示中は開いたまま）
+                WindowEvent::CloseRequested { api, .. } => {
+                    api.prevent_close();
+                    if !WINDOW_PINNED.load(Ordering::SeqCst) {
+                        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+                        let _ = window.hide();
+                    }
+                }
+                // 移動・リサイズしたら位置とサイズを保存
+                WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+                    schedule_window_bounds_save(window);
+                }
+                // OSのテーマが変わったら通知（テーマ設定がシステム連動の場合のみ）
+                WindowEvent::ThemeChanged(theme)
+                    if load_settings().theme == ThemeSetting::System =>
+                {
+                    let theme = if matches!(theme, tauri::Theme::Dark) {
+                        "dark"
+                    } else {
+                        "light"
+                    };
+                    let _ = window.app_handle().emit("system-theme-changed", theme);
+                }
+                _ => {}
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            hide_main_window,
+            get_last_shown_payload,
+            get_platform,
+            get_matched_apps,
+            get_recent_apps,
+            debug_active_window,
+            simulate_match,
+            list_installed_apps,
+            get_shortcuts_for_active,
+            get_shortcuts,
+            get_shortcuts_with_unbound,
+            get_shortcuts_sorted,
+            get_default_shortcut_sort,
+            get_shortcuts_for_app,
+            get_shortcuts_grouped,
+            get_last_shortcuts_diff,
+            warm_caches,
+            get_empty_actions,
+            get_policy_violations,
+            get_platform_shortcuts,
+            search_shortcuts,
+            suggest_action,
+            get_shortcuts_by_tags,
+            get_all_tags,
+            export_heatmap,
+            find_by_key_token,
+            rename_tag,
+            get_app_summaries,
+            get_coverage,
+            record_shortcut_use,
+            get_most_used_shortcuts,
+            show_top_shortcut,
+            get_quick_card,
+            toggle_favorite,
+            get_favorites,
+            open_config_file,
+            open_config_dir,
+            get_config_paths,
+            get_config_size_info,
+            open_app_docs,
+            open_settings_file,
+            open_keybindings_window,
+            close_keybindings_window,
+            get_app_version,
+            copy_shortcut,
+            open_about_window,
             close_about_window,
             get_theme_setting,
             set_theme_setting,
             get_system_theme,
+            get_language,
+            set_language,
+            get_primary_label,
+            get_escape_to_hide,
+            set_escape_to_hide,
+            set_primary_label,
+            export_shortcuts_text,
+            export_cheatsheet_markdown,
+            export_cheatsheet_html,
             show_overlay,
             hide_overlay,
+            get_overlay_layout_constants,
+            set_overlay_width_range,
+            pin_overlay,
+            unpin_overlay,
+            get_window_pinned,
+            activate_app,
+            set_window_pinned,
             save_overlay_position,
+            reset_setting,
+            check_hotkey_available,
+            set_hotkey,
+            set_hotkeys,
+            get_hotkey_errors,
+            get_hotkey_collisions,
+            get_display_hotkey,
+            get_config_errors,
+            verify_settings_cache,
+            export_settings,
+            import_settings,
+            validate_keybindings,
+            set_autostart,
+            get_autostart,
+            set_monitor_enabled,
             get_keybindings_raw,
             save_keybindings,
-            reset_keybindings
+            reset_keybindings,
+            reset_settings,
+            import_keybindings,
+            read_config_raw,
+            add_keybinding,
+            update_keybinding,
+            delete_keybinding,
+            write_config_raw
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // 終了時に監視スレッドを停止
+            if matches!(event, RunEvent::Exit) {
+                stop_active_window_monitor();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_config_wraps_unversioned_array() {
+        let v0 = serde_json::json!([
+            { "name": "Editor", "keybindings": [{ "action": "保存", "key": "Ctrl+S" }] }
+        ]);
+        let config = migrate_config(v0).unwrap();
+        assert_eq!(config.len(), 1);
+        assert_eq!(config[0].get_name(), "Editor");
+        assert_eq!(config[0].keybindings[0].action, "保存");
+    }
+
+    #[test]
+    fn migrate_config_keeps_current_version() {
+        let v1 = serde_json::json!({ "version": KEYBINDINGS_CONFIG_VERSION, "apps": [] });
+        assert!(migrate_config(v1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_config_rejects_newer_version() {
+        let future = serde_json::json!({ "version": KEYBINDINGS_CONFIG_VERSION + 1, "apps": [] });
+        assert!(migrate_config(future).is_err());
+    }
+
+    #[test]
+    fn unversioned_keybindings_file_is_rewritten_with_backup() {
+        let _config = isolated_config();
+        let v0 = "[\n  // エディタ\n  { name: \"Editor\", keybindings: [] },\n]\n";
+        write_config_file("keybindings.json", v0);
+        assert_eq!(load_keybindings_config()[0].get_name(), "Editor");
+        // 現在の形式で書き直し、元の内容はコメントも含めてそのまま残す
+        let path = get_keybindings_config_path().unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["version"], KEYBINDINGS_CONFIG_VERSION);
+        let backup = fs::read_to_string(with_file_suffix(&path, ".v0.bak")).unwrap();
+        assert_eq!(backup, v0);
+    }
+
+    /// JSON からアプリ設定を作成（テスト用）
+    fn app_config(value: serde_json::Value) -> AppConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn per_os_key_resolves_for_requested_os() {
+        let key: KeyValue = serde_json::from_value(
+            serde_json::json!({ "windows": "Ctrl+Shift+P", "macos": "Cmd+Shift+P" }),
+        )
+        .unwrap();
+        assert_eq!(
+            key.resolve(&OsType::Windows).as_deref(),
+            Some("Ctrl+Shift+P")
+        );
+        assert_eq!(key.resolve(&OsType::MacOS).as_deref(), Some("Cmd+Shift+P"));
+        // 指定のないOSと "-" は未割り当て
+        assert_eq!(key.resolve(&OsType::Linux), None);
+        let unbound: KeyValue = serde_json::from_value(serde_json::json!("-")).unwrap();
+        assert_eq!(unbound.resolve(&OsType::Linux), None);
+    }
+
+    #[test]
+    fn space_separated_keys_become_sequences() {
+        assert_eq!(normalize_key_sequence("g d"), "g -> d");
+        assert_eq!(normalize_key_sequence("Ctrl+K Ctrl+S"), "Ctrl+K -> Ctrl+S");
+        assert_eq!(
+            normalize_key_sequence("Ctrl + K Ctrl + S"),
+            "Ctrl+K -> Ctrl+S"
+        );
+        // 空白を含むキー名は順次入力とみなさない
+        assert_eq!(normalize_key_sequence("Page Down"), "Page Down");
+        // "Ctrl+Shift+Page" と "Down" に分かれ、"Down" が条件を満たさない
+        assert_eq!(
+            normalize_key_sequence("Ctrl + Shift + Page Down"),
+            "Ctrl + Shift + Page Down"
+        );
+    }
+
+    #[test]
+    fn key_step_arrays_resolve_as_sequences() {
+        let key: KeyValue =
+            serde_json::from_value(serde_json::json!(["Ctrl+X", "Ctrl+S"])).unwrap();
+        assert_eq!(
+            key,
+            KeyValue::Common(KeySteps::Sequence(vec![
+                "Ctrl+X".to_string(),
+                "Ctrl+S".to_string()
+            ]))
+        );
+        assert_eq!(
+            key.resolve(&OsType::Windows).as_deref(),
+            Some("Ctrl+X -> Ctrl+S")
+        );
+
+        // OS別の指定にも配列を書ける
+        let key: KeyValue =
+            serde_json::from_value(serde_json::json!({ "macos": ["g", "d"] })).unwrap();
+        assert_eq!(
+            key,
+            KeyValue::PerOs {
+                windows: None,
+                macos: Some(KeySteps::Sequence(vec!["g".to_string(), "d".to_string()])),
+                linux: None,
+            }
+        );
+        assert_eq!(key.resolve(&OsType::MacOS).as_deref(), Some("g -> d"));
+        assert_eq!(key.resolve(&OsType::Windows), None);
+        assert_eq!(key.resolve(&OsType::Linux), None);
+    }
+
+    /// ショートカットを作成（テスト用）
+    fn shortcut(app: &str, action: &str) -> NormalizedShortcut {
+        NormalizedShortcut {
+            app: app.to_string(),
+            icon: String::new(),
+            action: action.to_string(),
+            key: String::new(),
+            tags: Vec::new(),
+            description: None,
+            category: None,
+            unbound: false,
+        }
+    }
+
+    /// タグ付きのショートカットを作成（テスト用）
+    fn tagged(action: &str, tags: &[&str]) -> NormalizedShortcut {
+        NormalizedShortcut {
+            tags: tags.iter().map(ToString::to_string).collect(),
+            ..shortcut("Editor", action)
+        }
+    }
+
+    /// 設定ファイルを読み書きするテストの間保持し、終了時に設定ディレクトリを削除する
+    struct IsolatedConfig {
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl Drop for IsolatedConfig {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(get_config_dir().unwrap());
+            CONFIG_DIR_OVERRIDE.set(None);
+        }
+    }
+
+    /// 設定ファイルを読み書きするテストを直列化し、設定ディレクトリとキャッシュを空にする
+    /// 設定ディレクトリはこのテストのスレッドでのみ一時ディレクトリに差し替える
+    fn isolated_config() -> IsolatedConfig {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let guard = LOCK
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let dir = std::env::temp_dir().join(format!("finkey-test-{}", std::process::id()));
+        CONFIG_DIR_OVERRIDE.set(Some(dir));
+        let _ = fs::remove_dir_all(get_config_dir().unwrap());
+        *write_cache(&SETTINGS_CACHE) = None;
+        *write_cache(&KEYBINDINGS_CACHE) = None;
+        *write_cache(&KEYBINDINGS_DIR_CACHE) = None;
+        *write_cache(&EFFECTIVE_KEYBINDINGS_CACHE) = None;
+        *USAGE_CACHE.lock().unwrap() = None;
+        *FAVORITES_CACHE.lock().unwrap() = None;
+        *LAST_SHORTCUTS.lock().unwrap() = None;
+        set_config_error(&SETTINGS_CONFIG_ERROR, None);
+        set_config_error(&KEYBINDINGS_CONFIG_ERROR, None);
+        IsolatedConfig { _lock: guard }
+    }
+
+    /// 設定ディレクトリにファイルを書き込む（テスト用）
+    fn write_config_file(name: &str, content: &str) {
+        let dir = get_config_dir().unwrap();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(name), content).unwrap();
+    }
+
+    /// プロセス名のみのアクティブウィンドウ情報（テスト用）
+    fn window(process: &str) -> ActiveWindowInfo {
+        ActiveWindowInfo {
+            process: Some(process.to_string()),
+            ..ActiveWindowInfo::default()
+        }
+    }
+
+    #[test]
+    fn time_range_within_same_day() {
+        let start = parse_time_of_day("09:00").unwrap();
+        let end = parse_time_of_day("17:30").unwrap();
+        assert!(is_within_time_range(9 * 60, start, end));
+        assert!(is_within_time_range(12 * 60, start, end));
+        // 終了時刻は含まない
+        assert!(!is_within_time_range(17 * 60 + 30, start, end));
+        assert!(!is_within_time_range(8 * 60 + 59, start, end));
+    }
+
+    #[test]
+    fn time_range_wraps_past_midnight() {
+        let start = parse_time_of_day("22:00").unwrap();
+        let end = parse_time_of_day("07:00").unwrap();
+        assert!(is_within_time_range(22 * 60, start, end));
+        assert!(is_within_time_range(23 * 60 + 59, start, end));
+        assert!(is_within_time_range(0, start, end));
+        assert!(is_within_time_range(6 * 60 + 59, start, end));
+        assert!(!is_within_time_range(7 * 60, start, end));
+        assert!(!is_within_time_range(12 * 60, start, end));
+    }
+
+    #[test]
+    fn parse_time_of_day_rejects_invalid_values() {
+        assert_eq!(parse_time_of_day(" 7:05 "), Some(7 * 60 + 5));
+        assert_eq!(parse_time_of_day("24:00"), None);
+        assert_eq!(parse_time_of_day("12:60"), None);
+        assert_eq!(parse_time_of_day("noon"), None);
+    }
+
+    #[test]
+    fn suggest_action_corrects_typo() {
+        let shortcuts = vec![
+            shortcut("Git", "Commit"),
+            shortcut("Git", "Push"),
+            shortcut("Git", "Pull Request"),
+        ];
+        let suggestion = closest_action("comit", shortcuts.clone());
+        assert_eq!(suggestion, Some(shortcut("Git", "Commit")));
+        // 似ていないクエリには提案しない
+        assert_eq!(closest_action("xyz", shortcuts.clone()), None);
+        assert_eq!(closest_action("", shortcuts), None);
+    }
+
+    #[test]
+    fn suggest_action_threshold_is_inclusive() {
+        let shortcuts = vec![shortcut("Editor", "Paste")];
+        // 5文字中2文字違い（類似度 0.6）は提案し、3文字違い（0.4）は提案しない
+        assert!(closest_action("paxxe", shortcuts.clone()).is_some());
+        assert!(closest_action("pxxxe", shortcuts).is_none());
+    }
+
+    #[test]
+    fn suggest_action_prefers_earlier_on_tie() {
+        let shortcuts = vec![shortcut("A", "Cat"), shortcut("B", "Bat")];
+        // どちらも類似度は同じため設定順で先のもの
+        assert_eq!(closest_action("hat", shortcuts), Some(shortcut("A", "Cat")));
+    }
+
+    #[test]
+    fn match_apps_orders_by_priority() {
+        let config = [
+            app_config(serde_json::json!({ "name": "Editor", "bind": "code", "keybindings": [] })),
+            app_config(serde_json::json!({
+                "name": "VS Code",
+                "bind": "code",
+                "match_priority": 10,
+                "keybindings": []
+            })),
+            app_config(serde_json::json!({ "name": "Web", "bind": "chrome", "keybindings": [] })),
+            app_config(serde_json::json!({
+                "name": "Old Code",
+                "bind": ["code", "legacy"],
+                "match_priority": 20,
+                "disabled": true,
+                "keybindings": []
+            })),
+            app_config(
+                serde_json::json!({ "name": "Default", "fallback": true, "keybindings": [] }),
+            ),
+        ];
+        let names = |process: &str| -> Vec<String> {
+            match_apps(&window(process), &config)
+                .into_iter()
+                .map(|app| app.name)
+                .collect()
+        };
+        // マッチしたものはすべて返し、優先度の高いものを先にする（無効なアプリは除く）
+        assert_eq!(names("code"), ["VS Code", "Editor"]);
+        // 無効なアプリのみがマッチする場合はfallbackを表示
+        assert_eq!(names("legacy"), ["Default"]);
+    }
+
+    #[test]
+    fn filter_by_tags_any_or_all() {
+        let shortcuts = vec![
+            tagged("保存", &["file", "save"]),
+            tagged("開く", &["file"]),
+            tagged("検索", &["search"]),
+        ];
+        let actions = |shortcuts: Vec<NormalizedShortcut>| {
+            shortcuts
+                .into_iter()
+                .map(|shortcut| shortcut.action)
+                .collect::<Vec<_>>()
+        };
+        let tags = ["save".to_string(), "search".to_string()];
+        // OR: いずれかのタグを含むもの
+        assert_eq!(
+            actions(filter_by_tags(shortcuts.clone(), &tags, false)),
+            ["保存", "検索"]
+        );
+        // AND: すべてのタグを含むもの
+        let tags = ["file".to_string(), "save".to_string()];
+        assert_eq!(
+            actions(filter_by_tags(shortcuts.clone(), &tags, true)),
+            ["保存"]
+        );
+        // タグの指定が空なら絞り込まない
+        assert_eq!(
+            actions(filter_by_tags(shortcuts.clone(), &[], true)),
+            ["保存", "開く", "検索"]
+        );
+        assert_eq!(
+            actions(filter_by_tags(shortcuts, &[], false)),
+            ["保存", "開く", "検索"]
+        );
+    }
+
+    #[test]
+    fn reset_setting_restores_default() {
+        let defaults = AppSettings::default();
+        let mut settings = AppSettings {
+            overlay_duration: defaults.overlay_duration + 10,
+            ..AppSettings::default()
+        };
+        reset_setting_field(&mut settings, "overlay_duration").unwrap();
+        assert_eq!(settings.overlay_duration, defaults.overlay_duration);
+    }
+
+    #[test]
+    fn reset_setting_rejects_unknown_key() {
+        let mut settings = AppSettings {
+            overlay_duration: 42,
+            ..AppSettings::default()
+        };
+        assert!(reset_setting_field(&mut settings, "no_such_setting").is_err());
+        // 不明な項目では他の設定を変更しない
+        assert_eq!(settings.overlay_duration, 42);
+    }
+
+    #[test]
+    fn title_condition_filters_active_shortcuts() {
+        let config = [app_config(serde_json::json!({
+            "name": "Vim",
+            "bind": "vim",
+            "keybindings": [
+                { "action": "保存", "key": ":w" },
+                { "action": "単語移動", "key": "w", "title_contains": "- NORMAL" }
+            ]
+        }))];
+        let actions = |title: &str| {
+            let info = ActiveWindowInfo {
+                window: Some(title.to_string()),
+                ..window("vim")
+            };
+            shortcuts_for_window(&info, &config, KeyDisplayStyle::Verbose)
+                .into_iter()
+                .map(|shortcut| shortcut.action)
+                .collect::<Vec<_>>()
+        };
+        // タイトルの比較は大文字小文字を区別しない
+        assert_eq!(actions("main.rs - normal"), ["保存", "単語移動"]);
+        assert_eq!(actions("main.rs - INSERT"), ["保存"]);
+    }
+
+    #[test]
+    fn coverage_counts_bound_letters() {
+        let app = app_config(serde_json::json!({
+            "name": "Editor",
+            "keybindings": [
+                { "action": "全選択", "key": "Ctrl+A" },
+                { "action": "太字", "key": "ctrl + b" },
+                { "action": "全選択（別名）", "key": "Control+a" },
+                { "action": "コマンド", "key": "Ctrl+Shift+P" },
+                { "action": "ヘルプ", "key": "Ctrl+F1" }
+            ]
+        }));
+        let ratio = |modifier: &str| calculate_coverage(&app, modifier);
+        // 同じ文字は1回、修飾キーの組み合わせが異なるものや文字以外のキーは数えない
+        assert!((ratio("Ctrl") - 2.0 / 26.0).abs() < f64::EPSILON);
+        assert!((ratio("Shift+Ctrl") - 1.0 / 26.0).abs() < f64::EPSILON);
+        assert!(ratio("Hyper").abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn last_shown_payload_is_stored() {
+        let payload = WindowShownPayload {
+            active_window: Some(window("code")),
+            matched_apps: vec![normalized_app("Editor")],
+            escape_to_hide: false,
+        };
+        store_last_shown_payload(&payload);
+        let stored = get_last_shown_payload().unwrap();
+        assert_eq!(stored.active_window, payload.active_window);
+        assert_eq!(stored.matched_apps[0].name, "Editor");
+        assert!(!stored.escape_to_hide);
+    }
+
+    #[test]
+    fn overlay_width_adds_wide_emoji() {
+        let unclamped = (0.0, f64::MAX);
+        let ascii = calculate_overlay_width("", "", "Ctrl + A", unclamped);
+        let emoji = calculate_overlay_width("", "", "Ctrl + 🚀", unclamped);
+        // ASCII のみのキーは従来どおりの幅
+        let expected = OVERLAY_BASE_WIDTH
+            + OVERLAY_MODIFIER_WIDTH
+            + OVERLAY_SEPARATOR_WIDTH
+            + OVERLAY_DEFAULT_KEY_WIDTH;
+        assert!((ascii - expected).abs() < f64::EPSILON);
+        assert!((emoji - ascii - OVERLAY_WIDE_CHAR_EXTRA_WIDTH).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn overlay_width_fits_wide_labels() {
+        let unclamped = (0.0, f64::MAX);
+        let key_only = calculate_overlay_width("", "", "Ctrl + S", unclamped);
+        // 短いラベルはキーの幅のまま
+        let short = calculate_overlay_width("VS Code", "保存", "Ctrl + S", unclamped);
+        assert!((short - key_only).abs() < f64::EPSILON);
+        // 全角のラベルは文字数ではなく表示幅（2桁）で数える
+        let app = "ビジュアルスタジオコード";
+        let action = "すべてのファイルを保存";
+        let width = calculate_overlay_width(app, action, "Ctrl + S", unclamped);
+        let columns = (app.chars().count() + action.chars().count()) * 2;
+        #[allow(clippy::cast_precision_loss)]
+        let expected = (columns as f64).mul_add(OVERLAY_INFO_COLUMN_WIDTH, OVERLAY_INFO_BASE_WIDTH);
+        assert!(width > key_only);
+        assert!((width - expected).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn strict_config_surfaces_keybindings_error() {
+        let _config = isolated_config();
+        save_settings(&AppSettings {
+            strict_config: true,
+            ..AppSettings::default()
+        })
+        .unwrap();
+        write_config_file("keybindings.json", "{ \"version\": 1, \"apps\": [");
+        // デフォルト設定に戻さず、空の設定とエラーを返す
+        assert!(load_user_keybindings_config().is_empty());
+        let errors = get_config_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("keybindings.json"));
+    }
+
+    #[test]
+    fn strict_config_surfaces_settings_error() {
+        let _config = isolated_config();
+        write_config_file(
+            "settings.json",
+            r#"{ "strict_config": true, "overlay_duration": "long" }"#,
+        );
+        let settings = load_settings();
+        assert!(get_config_errors()
+            .iter()
+            .any(|e| e.contains("settings.json")));
+        // ファイルはデフォルトで上書きしない
+        let content = fs::read_to_string(get_settings_path().unwrap()).unwrap();
+        assert!(content.contains("\"long\""));
+        assert_eq!(
+            settings.overlay_duration,
+            AppSettings::default().overlay_duration
+        );
+    }
+
+    #[test]
+    fn lenient_config_falls_back_to_defaults() {
+        let _config = isolated_config();
+        write_config_file("settings.json", r#"{ "overlay_duration": "long" }"#);
+        load_settings();
+        assert!(get_config_errors().is_empty());
+        // 読み込めない設定はデフォルトで書き直される
+        let content = fs::read_to_string(get_settings_path().unwrap()).unwrap();
+        assert!(!content.contains("\"long\""));
+    }
+
+    #[test]
+    fn overlay_timer_waits_while_pinned() {
+        static PINNED: PinFlag = PinFlag::new();
+        PINNED.set(true);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let waiter = thread::spawn(move || {
+            PINNED.wait_while_pinned();
+            tx.send(()).unwrap();
+        });
+        // 固定中は非表示に進まない
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+        PINNED.set(false);
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+        waiter.join().unwrap();
+    }
+
+    #[test]
+    fn overlay_pin_is_not_kept_after_hide() {
+        static PINNED: PinFlag = PinFlag::new();
+        // 固定したまま非表示にする（hide_overlay と同じく固定を解除する）
+        PINNED.set(true);
+        assert!(PINNED.is_pinned());
+        PINNED.set(false);
+        // 次のオーバーレイは固定されておらず、タイマーは待機せずに進む
+        assert!(!PINNED.is_pinned());
+        let (tx, rx) = std::sync::mpsc::channel();
+        let waiter = thread::spawn(move || {
+            PINNED.wait_while_pinned();
+            tx.send(()).unwrap();
+        });
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+        waiter.join().unwrap();
+    }
+
+    #[test]
+    fn rename_tag_replaces_and_merges() {
+        let mut config = vec![app_config(serde_json::json!({
+            "name": "Editor",
+            "keybindings": [
+                { "action": "戻る", "key": "Ctrl+B", "tags": ["nav"] },
+                { "action": "ジャンプ", "key": "Ctrl+J", "tags": ["navigation", "nav", "edit"] },
+                { "action": "保存", "key": "Ctrl+S", "tags": ["edit"] }
+            ]
+        }))];
+        assert_eq!(rename_tag_in_config(&mut config, "nav", "navigation"), 2);
+        let tags: Vec<_> = config[0]
+            .keybindings
+            .iter()
+            .map(|kb| kb.tags.clone())
+            .collect();
+        assert_eq!(tags[0], ["navigation"]);
+        // 既に新しいタグを持つ場合は重複させずに統合する
+        assert_eq!(tags[1], ["navigation", "edit"]);
+        assert_eq!(tags[2], ["edit"]);
+    }
+
+    #[test]
+    fn overlay_layout_constants_match_module_values() {
+        let _config = isolated_config();
+        // フロントエンドに公開しているレイアウトの値
+        let constants = get_overlay_layout_constants();
+        let pairs = [
+            (constants.base_width, 150.0),
+            (constants.modifier_width, 50.0),
+            (constants.separator_width, 20.0),
+            (constants.sequence_separator_width, 30.0),
+            (constants.default_key_width, 30.0),
+            (constants.wide_char_extra_width, 15.0),
+            (constants.info_base_width, 80.0),
+            (constants.info_column_width, 8.0),
+            (constants.min_width, 200.0),
+            (constants.max_width, 600.0),
+            (constants.height, 150.0),
+        ];
+        for (actual, expected) in pairs {
+            assert!((actual - expected).abs() < f64::EPSILON);
+        }
+
+        // 最小幅・最大幅は設定を反映し、幅の計算と一致する
+        set_overlay_width_range(Some(400.0), Some(420.0)).unwrap();
+        let constants = get_overlay_layout_constants();
+        let range = (constants.min_width, constants.max_width);
+        assert!((calculate_overlay_width("", "", "A", range) - 400.0).abs() < f64::EPSILON);
+        assert!((constants.max_width - 420.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn sort_key_ignores_emoji_prefix() {
+        let mut apps = [
+            app_config(serde_json::json!({ "name": "Browser", "keybindings": [] })),
+            app_config(serde_json::json!({ "name": "🚀 Alfred", "keybindings": [] })),
+            app_config(serde_json::json!({ "name": "  ✏️ Code", "keybindings": [] })),
+        ];
+        assert_eq!(apps[1].sort_key(), "alfred");
+        apps.sort_by_key(AppConfig::sort_key);
+        let names: Vec<String> = apps.iter().map(AppConfig::get_name).collect();
+        assert_eq!(names, ["🚀 Alfred", "Browser", "  ✏️ Code"]);
+    }
+
+    #[test]
+    fn text_export_follows_primary_label() {
+        let shortcuts = [NormalizedShortcut {
+            key: "Ctrl+S".to_string(),
+            ..shortcut("Editor", "保存")
+        }];
+        assert_eq!(
+            format_shortcuts_text(&shortcuts, PrimaryLabel::Action),
+            "Editor\t保存\tCtrl+S"
+        );
+        assert_eq!(
+            format_shortcuts_text(&shortcuts, PrimaryLabel::Key),
+            "Editor\tCtrl+S\t保存"
+        );
+    }
+
+    #[test]
+    fn sort_shortcuts_ignores_case_and_keeps_ties_in_order() {
+        let keyed = |app: &str, action: &str, key: &str| NormalizedShortcut {
+            key: key.to_string(),
+            ..shortcut(app, action)
+        };
+        let shortcuts = vec![
+            keyed("Editor", "B", "Ctrl+B"),
+            keyed("browser", "a", "Ctrl+A"),
+            keyed("Editor", "c", "Ctrl+A"),
+        ];
+        let sorted = |by| {
+            let mut sorted = shortcuts.clone();
+            sort_shortcuts(&mut sorted, by);
+            sorted
+                .into_iter()
+                .map(|shortcut| shortcut.action)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(ShortcutSort::Config), ["B", "a", "c"]);
+        // 大文字小文字を区別せずに比較し、同じ値は設定順のまま
+        assert_eq!(sorted(ShortcutSort::Action), ["a", "B", "c"]);
+        assert_eq!(sorted(ShortcutSort::App), ["a", "B", "c"]);
+        assert_eq!(sorted(ShortcutSort::Key), ["a", "c", "B"]);
+    }
+
+    #[test]
+    fn cheatsheet_html_escapes_cells_and_search_text() {
+        assert_eq!(
+            escape_html(r#"<script>"&'"#),
+            "&lt;script&gt;&quot;&amp;&#39;"
+        );
+        let shortcuts = [NormalizedShortcut {
+            key: "Ctrl+S".to_string(),
+            tags: vec!["<b>".to_string()],
+            ..shortcut("<Editor>", r#"<script>"&'"#)
+        }];
+        let icons = HashMap::from([("<Editor>".to_string(), "📝".to_string())]);
+        let html = format_cheatsheet_html(&shortcuts, &icons, PrimaryLabel::Action);
+        assert!(html.contains("<summary>📝 &lt;Editor&gt;</summary>"));
+        // セルと検索用の属性の両方でエスケープする（検索用の文字列は小文字）
+        assert!(html.contains(concat!(
+            "<tr data-search=\"&lt;script&gt;&quot;&amp;&#39; ctrl+s &lt;b&gt;\">",
+            "<td>&lt;script&gt;&quot;&amp;&#39;</td><td><kbd>Ctrl+S</kbd></td></tr>"
+        )));
+        assert!(!html.contains(r#"<script>""#));
+    }
+
+    #[test]
+    fn empty_actions_are_reported_and_excluded() {
+        let apps = [app_config(serde_json::json!({
+            "name": "Editor",
+            "keybindings": [
+                { "action": "保存", "key": "Ctrl+S" },
+                { "action": "  ", "key": "Ctrl+E" }
+            ]
+        }))];
+        assert_eq!(find_empty_actions(&apps), ["Editor: Ctrl+E"]);
+        let actions: Vec<String> = normalize_shortcuts(apps, KeyDisplayStyle::Verbose)
+            .into_iter()
+            .map(|shortcut| shortcut.action)
+            .collect();
+        assert_eq!(actions, ["保存"]);
+    }
+
+    #[test]
+    fn app_path_keys_become_sorted_base_names() {
+        let keys = [
+            "chrome.exe",
+            "Code.EXE",
+            "7zFM.exe",
+            "CHROME.exe",
+            " ",
+            "wordpad",
+        ]
+        .map(ToString::to_string)
+        .to_vec();
+        // 拡張子を除き、大文字小文字違いの重複は1つにまとめる
+        assert_eq!(
+            app_names_from_app_paths(keys),
+            ["7zFM", "chrome", "Code", "wordpad"]
+        );
+    }
+
+    #[test]
+    fn shortcuts_diff_detects_added_removed_and_changed() {
+        let keyed = |action: &str, key: &str| NormalizedShortcut {
+            key: key.to_string(),
+            ..shortcut("Editor", action)
+        };
+        let previous = [
+            keyed("保存", "Ctrl+S"),
+            keyed("検索", "Ctrl+F"),
+            keyed("閉じる", "Ctrl+W"),
+        ];
+        let current = [
+            keyed("保存", "Ctrl+S"),
+            keyed("検索", "Ctrl+Shift+F"),
+            keyed("開く", "Ctrl+O"),
+        ];
+        let diff = diff_shortcuts(&previous, &current);
+        assert_eq!(diff.added, [keyed("開く", "Ctrl+O")]);
+        assert_eq!(diff.removed, [keyed("閉じる", "Ctrl+W")]);
+        assert_eq!(diff.changed, [keyed("検索", "Ctrl+Shift+F")]);
+        assert!(diff_shortcuts(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn warm_caches_populates_caches() {
+        let _config = isolated_config();
+        warm_caches();
+        assert!(read_cache(&SETTINGS_CACHE).is_some());
+        assert!(read_cache(&KEYBINDINGS_CACHE).is_some());
+        assert!(USAGE_CACHE.lock().unwrap().is_some());
+        assert!(FAVORITES_CACHE.lock().unwrap().is_some());
+        assert!(LAST_SHORTCUTS.lock().unwrap().is_some());
+        // 繰り返し呼び出しても安全
+        warm_caches();
+        assert!(read_cache(&KEYBINDINGS_CACHE).is_some());
+    }
+
+    #[test]
+    fn usage_and_favorites_round_trip() {
+        let _config = isolated_config();
+        let config_dir = get_config_dir();
+        // 同時に記録しても互いの回数を上書きしない
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                let config_dir = config_dir.clone();
+                scope.spawn(move || {
+                    CONFIG_DIR_OVERRIDE.set(config_dir);
+                    for _ in 0..10 {
+                        record_shortcut_use("保存".to_string(), "Editor".to_string()).unwrap();
+                    }
+                });
+            }
+        });
+        assert!(toggle_favorite("Editor".to_string(), "保存".to_string()).unwrap());
+
+        // キャッシュを捨ててファイルから読み直しても同じ内容
+        *USAGE_CACHE.lock().unwrap() = None;
+        *FAVORITES_CACHE.lock().unwrap() = None;
+        let usage = load_usage();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].count, 40);
+        let favorite = FavoriteEntry {
+            app: "Editor".to_string(),
+            action: "保存".to_string(),
+        };
+        assert_eq!(load_favorites(), [favorite]);
+        assert!(!toggle_favorite("Editor".to_string(), "保存".to_string()).unwrap());
+        assert!(load_favorites().is_empty());
+        // 一時ファイルは残らない
+        assert!(!with_file_suffix(&get_usage_path().unwrap(), ".tmp").exists());
+    }
+
+    #[test]
+    fn broken_usage_file_is_kept_and_not_overwritten() {
+        let _config = isolated_config();
+        write_config_file("usage.json", "{ broken");
+        let path = get_usage_path().unwrap();
+
+        // 読み込みは空として扱い、バックアップを残す
+        assert!(load_usage().is_empty());
+        assert_eq!(
+            fs::read_to_string(with_file_suffix(&path, ".broken")).unwrap(),
+            "{ broken"
+        );
+        // 修正されるまで記録は拒否し、元のファイルはそのまま
+        assert!(record_shortcut_use("保存".to_string(), "Editor".to_string()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ broken");
+    }
+
+    #[test]
+    fn global_app_is_shown_without_match() {
+        let config = [
+            app_config(serde_json::json!({
+                "name": "Editor",
+                "bind": "code",
+                "keybindings": [{ "action": "保存", "key": "Ctrl+S" }]
+            })),
+            app_config(serde_json::json!({
+                "name": "Global",
+                "global": true,
+                "keybindings": [{ "action": "起動", "key": "Alt+Space" }]
+            })),
+        ];
+        let names = |process: &str| -> Vec<String> {
+            shortcuts_for_window(&window(process), &config, KeyDisplayStyle::Verbose)
+                .into_iter()
+                .map(|shortcut| shortcut.app)
+                .collect()
+        };
+        assert_eq!(names("other"), ["Global"]);
+        // マッチしたアプリがある場合も global なアプリを先頭に表示
+        assert_eq!(names("code"), ["Global", "Editor"]);
+    }
+
+    #[test]
+    fn top_shortcut_ignores_global_apps() {
+        let config = [
+            app_config(serde_json::json!({
+                "name": "Launcher",
+                "global": true,
+                "keybindings": [{ "action": "起動", "key": "Alt+Space" }]
+            })),
+            app_config(serde_json::json!({
+                "name": "Editor",
+                "bind": "code",
+                "keybindings": [
+                    { "action": "保存", "key": "Ctrl+S" },
+                    { "action": "検索", "key": "Ctrl+F" }
+                ]
+            })),
+        ];
+        let apps = matched_non_global_apps(&window("code"), &config);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].get_name(), "Editor");
+        // マッチするアプリがなければ global なアプリも対象にしない
+        assert!(matched_non_global_apps(&window("other"), &config).is_empty());
+    }
+
+    #[test]
+    fn top_shortcut_prefers_usage_over_config_order() {
+        let shortcuts = vec![shortcut("Editor", "保存"), shortcut("Editor", "検索")];
+        let used = |action: &str, count| UsageEntry {
+            app: "Editor".to_string(),
+            action: action.to_string(),
+            count,
+        };
+        // よく使うものが設定順より優先される
+        let top = pick_top_shortcut(shortcuts.clone(), vec![used("保存", 1), used("検索", 3)]);
+        assert_eq!(top, Some(shortcut("Editor", "検索")));
+        // 使用履歴がなければ（設定にないものだけでも）先頭のショートカット
+        assert_eq!(
+            pick_top_shortcut(shortcuts.clone(), Vec::new()),
+            Some(shortcut("Editor", "保存"))
+        );
+        assert_eq!(
+            pick_top_shortcut(shortcuts, vec![used("削除", 5)]),
+            Some(shortcut("Editor", "保存"))
+        );
+        assert_eq!(pick_top_shortcut(Vec::new(), Vec::new()), None);
+    }
+
+    /// 名前のみのアプリ情報（テスト用）
+    fn normalized_app(name: &str) -> NormalizedApp {
+        NormalizedApp {
+            name: name.to_string(),
+            icon: String::new(),
+            icon_url: None,
+            url: None,
+        }
+    }
+
+    /// 最近使ったアプリの名前を新しい順に取得（テスト用）
+    fn recent_names(recent: &[(NormalizedApp, SystemTime)], limit: usize) -> Vec<String> {
+        newest_recent_apps(recent, limit)
+            .into_iter()
+            .map(|app| app.name)
+            .collect()
+    }
+
+    #[test]
+    fn recent_apps_are_newest_first() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let mut recent = Vec::new();
+        push_recent_apps(&mut recent, &[normalized_app("Editor")], at(0));
+        push_recent_apps(&mut recent, &[normalized_app("Browser")], at(1));
+        assert_eq!(recent_names(&recent, 10), ["Browser", "Editor"]);
+
+        // 再びマッチしたアプリは時刻が更新されて先頭になり、重複しない
+        push_recent_apps(&mut recent, &[normalized_app("Editor")], at(2));
+        assert_eq!(recent_names(&recent, 10), ["Editor", "Browser"]);
+        assert_eq!(recent_names(&recent, 1), ["Editor"]);
+    }
+
+    #[test]
+    fn tray_tooltip_follows_language() {
+        let info = ActiveWindowInfo {
+            process: Some("Code".to_string()),
+            window: Some("x".repeat(61)),
+            ..ActiveWindowInfo::default()
+        };
+        let title = format!("{}…", "x".repeat(60));
+        assert_eq!(
+            format_tray_tooltip(Some(&info), Language::En),
+            format!("Finkey — active: Code / {title}")
+        );
+        assert_eq!(
+            format_tray_tooltip(None, Language::Ja),
+            "Finkey — アクティブ: - / -"
+        );
+    }
+
+    #[test]
+    fn icon_cache_file_name_is_stable() {
+        assert_eq!(fnv1a_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        // ツールチェーンに依存しない固定のファイル名（大文字小文字は区別しない）
+        let name = icon_cache_file_name("/opt/code/code.exe").unwrap();
+        assert_eq!(name, "code-5f578cc6cb58e2c5.png");
+        assert_eq!(icon_cache_file_name("/OPT/Code/Code.exe").unwrap(), name);
+        assert!(icon_cache_file_name("").is_none());
+    }
+
+    #[test]
+    fn auto_icon_uses_cache_and_falls_back() {
+        let icons_dir = std::env::temp_dir().join(format!("finkey-icons-{}", std::process::id()));
+        let missing = icons_dir.join("missing").join("app.exe");
+        let missing = missing.to_string_lossy();
+        // 抽出できなければ None（設定のアイコンを使用）となり、ファイルも残らない
+        assert!(resolve_auto_icon_in(&icons_dir, &missing).is_none());
+        let cached = icons_dir.join(icon_cache_file_name(&missing).unwrap());
+        assert!(!cached.exists());
+        assert!(cached_auto_icon_url_in(&icons_dir, &missing).is_none());
+        // キャッシュ済みのファイルがあれば抽出せずにそのパスを返す
+        fs::write(&cached, b"png").unwrap();
+        assert_eq!(
+            resolve_auto_icon_in(&icons_dir, &missing),
+            Some(cached.to_string_lossy().into_owned())
+        );
+        // フロントエンドには data URL で渡す
+        assert_eq!(
+            cached_auto_icon_url_in(&icons_dir, &missing).as_deref(),
+            Some("data:image/png;base64,cG5n")
+        );
+        let _ = fs::remove_dir_all(&icons_dir);
+    }
+
+    #[test]
+    fn validation_flags_unreachable_platform_key() {
+        let _config = isolated_config();
+        write_config_file(
+            "keybindings.json",
+            r#"{ "version": 1, "apps": [{
+                "name": "Finder",
+                "os": "macos",
+                "keybindings": [
+                    { "action": "新規フォルダ", "key": { "windows": "Ctrl+Shift+N" } },
+                    { "action": "情報を見る", "key": { "macos": "Cmd+I" } },
+                    { "action": "コピー", "key": "Cmd+C" }
+                ]
+            }] }"#,
+        );
+        assert_eq!(
+            validate_keybindings(),
+            Ok(vec![ConfigWarning::MissingOsKey {
+                app: "macOS".to_string(),
+                action: "新規フォルダ".to_string(),
+                os: OsType::MacOS,
+            }])
+        );
+    }
+
+    #[test]
+    fn raw_config_round_trips_verbatim() {
+        let _config = isolated_config();
+        // 書式を保持
+        let content = "{\n  \"version\": 1,\n  \"apps\": [{ \"name\": \"Editor\", \"keybindings\": [ ] }]\n}\n";
+        write_config_raw(content.to_string()).unwrap();
+        assert_eq!(read_config_raw().unwrap(), content);
+        // パースできない内容は書き込まない
+        assert!(write_config_raw("{ \"apps\": [".to_string()).is_err());
+        assert_eq!(read_config_raw().unwrap(), content);
+    }
+
+    #[test]
+    fn raw_config_accepts_json5() {
+        let _config = isolated_config();
+        // コメント・末尾のカンマ・引用符なしのキーを含む内容もそのまま保存して読み込める
+        let content = "[\n  // エディタ\n  { name: \"Editor\", keybindings: [], },\n]\n";
+        write_config_raw(content.to_string()).unwrap();
+        assert_eq!(read_config_raw().unwrap(), content);
+        let names: Vec<String> = load_keybindings_config()
+            .iter()
+            .map(AppConfig::get_name)
+            .collect();
+        assert_eq!(names, ["Editor"]);
+    }
+
+    #[test]
+    fn json_config_takes_precedence_over_yaml() {
+        let _config = isolated_config();
+        write_config_file("settings.yaml", "language: en\n");
+        write_config_file("settings.json", r#"{ "language": "ja" }"#);
+        write_config_file(
+            "keybindings.yaml",
+            "version: 1\napps:\n  - name: Yaml\n    keybindings: []\n",
+        );
+        write_config_file(
+            "keybindings.json",
+            r#"{ "version": 1, "apps": [{ "name": "Json", "keybindings": [] }] }"#,
+        );
+        // 両方ある場合はJSONを使う
+        assert_eq!(load_settings().language, LanguageSetting::Ja);
+        let names: Vec<String> = load_user_keybindings_config()
+            .iter()
+            .map(AppConfig::get_name)
+            .collect();
+        assert_eq!(names, ["Json"]);
+    }
+
+    #[test]
+    fn yaml_config_is_written_back_as_yaml() {
+        let _config = isolated_config();
+        write_config_file("settings.yaml", "language: en\n");
+        write_config_file("keybindings.yaml", "version: 1\napps: []\n");
+
+        let mut settings = load_settings();
+        assert_eq!(settings.language, LanguageSetting::En);
+        settings.language = LanguageSetting::Ja;
+        save_settings(&settings).unwrap();
+        let config = vec![app_config(
+            serde_json::json!({ "name": "Editor", "keybindings": [] }),
+        )];
+        save_keybindings_config(&config).unwrap();
+
+        // YAMLしかない場合はYAMLのファイルを更新し、JSONのファイルは作らない
+        let dir = get_config_dir().unwrap();
+        assert!(!dir.join("settings.json").exists());
+        assert!(!dir.join("keybindings.json").exists());
+        let saved: serde_json::Value =
+            serde_yaml_ng::from_str(&fs::read_to_string(dir.join("settings.yaml")).unwrap())
+                .unwrap();
+        assert_eq!(saved["language"], "ja");
+        let names: Vec<String> = load_user_keybindings_config()
+            .iter()
+            .map(AppConfig::get_name)
+            .collect();
+        assert_eq!(names, ["Editor"]);
+    }
+
+    #[test]
+    fn overlay_payload_carries_app_color() {
+        let config = [
+            app_config(
+                serde_json::json!({ "name": "Editor", "color": "#3b82f6", "keybindings": [] }),
+            ),
+            app_config(serde_json::json!({ "name": "Web", "keybindings": [] })),
+        ];
+        assert_eq!(find_app_color(&config, "Web"), None);
+        let payload = OverlayPayload {
+            app_name: "Editor".to_string(),
+            action_name: "保存".to_string(),
+            shortcut_key: "Ctrl + S".to_string(),
+            duration: 2,
+            theme: "light".to_string(),
+            app_color: find_app_color(&config, "Editor"),
+            fade_ms: OVERLAY_FADE_MS,
+        };
+        let value = serde_json::to_value(payload).unwrap();
+        assert_eq!(value["app_color"], "#3b82f6");
+    }
+
+    #[test]
+    fn hotkey_collisions_detect_same_combo() {
+        let settings = AppSettings {
+            hotkey: "Ctrl+Shift+Space".to_string(),
+            hotkeys: vec!["Alt+K".to_string(), "Shift+Ctrl+Space".to_string()],
+            ..AppSettings::default()
+        };
+        assert_eq!(
+            settings.hotkey_collisions(),
+            ["hotkey（Ctrl+Shift+Space） / hotkeys[1]（Shift+Ctrl+Space）"]
+        );
+        let settings = AppSettings {
+            hotkeys: vec!["Alt+K".to_string()],
+            ..settings
+        };
+        assert!(settings.hotkey_collisions().is_empty());
+    }
+
+    #[test]
+    fn policy_violations_only_when_required() {
+        let shortcuts = [tagged("保存", &["file"]), tagged("検索", &[])];
+        assert!(find_policy_violations(&shortcuts, false).is_empty());
+        assert_eq!(find_policy_violations(&shortcuts, true), ["Editor: 検索"]);
+    }
+
+    #[test]
+    fn overlay_width_respects_custom_bounds() {
+        let _config = isolated_config();
+        let long_key = "Ctrl + Shift + Alt + Win + PageDown";
+        set_overlay_width_range(Some(400.0), Some(420.0)).unwrap();
+        let range = load_settings().overlay_width_range();
+        assert!((calculate_overlay_width("", "", "A", range) - 400.0).abs() < f64::EPSILON);
+        assert!((calculate_overlay_width("", "", long_key, range) - 420.0).abs() < f64::EPSILON);
+
+        // 最小 > 最大や範囲外の値は保存しない
+        assert!(set_overlay_width_range(Some(900.0), Some(800.0)).is_err());
+        assert!(set_overlay_width_range(Some(50.0), None).is_err());
+        assert!(set_overlay_width_range(None, Some(5000.0)).is_err());
+        assert_eq!(load_settings().overlay_width_range(), range);
+    }
+
+    #[test]
+    fn settings_export_excludes_machine_specific_fields() {
+        let _config = isolated_config();
+        save_settings(&AppSettings {
+            overlay_position: OverlayPosition {
+                x: Some(10),
+                y: Some(20),
+            },
+            window_bounds: Some(WindowBounds {
+                x: 0,
+                y: 0,
+                width: 800,
+                height: 600,
+            }),
+            autostart: true,
+            ..AppSettings::default()
+        })
+        .unwrap();
+        let exported: serde_json::Value = serde_json::from_str(&export_settings()).unwrap();
+        for key in MACHINE_SPECIFIC_SETTINGS {
+            assert!(exported.get(key).is_none(), "{key} が含まれています");
+        }
+        assert!(exported.get("hotkey").is_some());
+    }
+
+    #[test]
+    fn key_display_style_maps_modifiers_on_macos() {
+        let key = "Command + Shift + K → Option + Control + A";
+        assert_eq!(
+            apply_key_display_style_for(key, KeyDisplayStyle::Symbols, &OsType::MacOS),
+            "⌘ + ⇧ + K → ⌥ + ⌃ + A"
+        );
+        assert_eq!(
+            apply_key_display_style_for(key, KeyDisplayStyle::Verbose, &OsType::MacOS),
+            key
+        );
+        // macOS 以外は記号にしない
+        assert_eq!(
+            apply_key_display_style_for(
+                "Ctrl + Shift + K",
+                KeyDisplayStyle::Symbols,
+                &OsType::Windows
+            ),
+            "Ctrl + Shift + K"
+        );
+    }
+
+    #[test]
+    fn display_hotkey_uses_symbols_on_macos() {
+        let settings = AppSettings {
+            hotkey: "Command+Shift+K".to_string(),
+            key_display_style: KeyDisplayStyle::Symbols,
+            ..AppSettings::default()
+        };
+        assert_eq!(display_hotkey(&settings, &OsType::MacOS), "⌘ + ⇧ + K");
+        let settings = AppSettings {
+            key_display_style: KeyDisplayStyle::Verbose,
+            ..settings
+        };
+        assert_eq!(
+            display_hotkey(&settings, &OsType::MacOS),
+            "Command + Shift + K"
+        );
+    }
+
+    #[test]
+    fn fallback_apps_only_shown_without_match() {
+        let other_os = if cfg!(target_os = "macos") {
+            "windows"
+        } else {
+            "macos"
+        };
+        let config = [
+            app_config(
+                serde_json::json!({ "name": "Desktop", "fallback": true, "keybindings": [] }),
+            ),
+            app_config(
+                serde_json::json!({ "name": "Generic", "fallback": true, "keybindings": [] }),
+            ),
+            app_config(serde_json::json!({ "name": "Editor", "bind": "code", "keybindings": [] })),
+            app_config(serde_json::json!({ "os": other_os, "bind": "legacy", "keybindings": [] })),
+        ];
+        let names = |process: &str| -> Vec<String> {
+            match_apps(&window(process), &config)
+                .into_iter()
+                .map(|app| app.name)
+                .collect()
+        };
+        assert_eq!(names("code"), ["Editor"]);
+        // マッチするアプリがなければ fallback なアプリをすべて表示
+        assert_eq!(names("other"), ["Desktop", "Generic"]);
+        // 他OS向けのアプリのみがマッチする場合も fallback なアプリを表示
+        assert_eq!(names("legacy"), ["Desktop", "Generic"]);
+    }
+
+    #[test]
+    fn owner_window_used_only_without_foreground_match() {
+        let other_os = if cfg!(target_os = "macos") {
+            "windows"
+        } else {
+            "macos"
+        };
+        let config = [
+            app_config(
+                serde_json::json!({ "name": "Desktop", "fallback": true, "keybindings": [] }),
+            ),
+            app_config(serde_json::json!({ "name": "Editor", "bind": "code", "keybindings": [] })),
+            app_config(
+                serde_json::json!({ "name": "Picker", "bind": "picker", "keybindings": [] }),
+            ),
+            app_config(serde_json::json!({ "os": other_os, "bind": "legacy", "keybindings": [] })),
+        ];
+        let dialog = |process: &str, owner: &str| ActiveWindowInfo {
+            owner: Some(Box::new(window(owner))),
+            ..window(process)
+        };
+        let names = |info: &ActiveWindowInfo| -> Vec<String> {
+            match_apps(info, &config)
+                .into_iter()
+                .map(|app| app.name)
+                .collect()
+        };
+
+        // 最前面のウィンドウに fallback 以外のマッチがなければ所有者を使う
+        let info = dialog("dialog", "code");
+        let (target, has_match) = match_context(&info, &config);
+        assert!(std::ptr::eq(target, info.owner.as_deref().unwrap()));
+        assert!(has_match);
+        assert_eq!(names(&info), ["Editor"]);
+
+        // 最前面のウィンドウにマッチするアプリがあれば所有者は使わない
+        let info = dialog("picker", "code");
+        assert!(std::ptr::eq(match_target(&info, config.iter()), &info));
+        assert_eq!(names(&info), ["Picker"]);
+
+        // 所有者にもマッチしなければ最前面のウィンドウのまま fallback を表示
+        let info = dialog("dialog", "other");
+        let (target, has_match) = match_context(&info, &config);
+        assert!(std::ptr::eq(target, &info));
+        assert!(!has_match);
+        assert_eq!(names(&info), ["Desktop"]);
+
+        // 所有者にマッチするのが他OS向けのアプリだけなら所有者は使わない
+        let info = dialog("dialog", "legacy");
+        assert!(std::ptr::eq(match_context(&info, &config).0, &info));
+        assert_eq!(names(&info), ["Desktop"]);
+    }
+
+    #[test]
+    fn heatmap_counts_keys_and_modifiers() {
+        let keyed = |key: &str| NormalizedShortcut {
+            key: key.to_string(),
+            ..shortcut("Editor", key)
+        };
+        let (keys, modifiers) = count_key_usage(&[
+            keyed("Ctrl + K → Ctrl + S"),
+            keyed("Ctrl + Shift + k"),
+            keyed("F12"),
+        ]);
+        assert_eq!(
+            keys,
+            BTreeMap::from([
+                ("F12".to_string(), 1),
+                ("K".to_string(), 2),
+                ("S".to_string(), 1)
+            ])
+        );
+        assert_eq!(
+            modifiers,
+            BTreeMap::from([("ctrl".to_string(), 3), ("shift".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn stale_settings_cache_is_repaired() {
+        let _config = isolated_config();
+        save_settings(&AppSettings {
+            hotkey: "Alt+K".to_string(),
+            ..AppSettings::default()
+        })
+        .unwrap();
+        load_settings();
+        assert!(verify_settings_cache());
+
+        // 更新時刻が食い違い、内容も古いキャッシュを作る
+        if let Some(cache) = write_cache(&SETTINGS_CACHE).as_mut() {
+            cache.data.hotkey = "Alt+J".to_string();
+            cache.last_modified = Some(SystemTime::UNIX_EPOCH);
+        }
+        assert!(!verify_settings_cache());
+        assert_eq!(load_settings().hotkey, "Alt+K");
+        assert!(verify_settings_cache());
+    }
+
+    #[test]
+    fn keybinding_icon_overrides_app_icon() {
+        let apps = [app_config(serde_json::json!({
+            "name": "Editor",
+            "icon": "📝",
+            "keybindings": [
+                { "action": "保存", "key": "Ctrl+S", "icon": "💾" },
+                { "action": "検索", "key": "Ctrl+F" }
+            ]
+        }))];
+        let icons: Vec<String> = normalize_shortcuts(apps, KeyDisplayStyle::Verbose)
+            .into_iter()
+            .map(|shortcut| shortcut.icon)
+            .collect();
+        assert_eq!(icons, ["💾", "📝"]);
+    }
+
+    #[test]
+    fn monitor_iteration_honours_enabled_flag() {
+        let enabled = MONITOR_ENABLED.load(Ordering::SeqCst);
+        let visible = WINDOW_VISIBLE.load(Ordering::SeqCst);
+        WINDOW_VISIBLE.store(false, Ordering::SeqCst);
+
+        MONITOR_ENABLED.store(false, Ordering::SeqCst);
+        let mut updated = false;
+        let interval = run_monitor_iteration(|| updated = true);
+        assert!(!updated);
+        assert_eq!(interval, Duration::from_secs(1));
+
+        MONITOR_ENABLED.store(true, Ordering::SeqCst);
+        let interval = run_monitor_iteration(|| updated = true);
+        assert!(updated);
+        assert_eq!(interval, Duration::from_millis(200));
+
+        MONITOR_ENABLED.store(enabled, Ordering::SeqCst);
+        WINDOW_VISIBLE.store(visible, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn key_token_matches_whole_segments() {
+        assert!(key_contains_token("Ctrl + F5", "F5"));
+        assert!(key_contains_token("Ctrl+f5", "F5"));
+        assert!(key_contains_token("Ctrl + K → F5", "F5"));
+        assert!(!key_contains_token("F50", "F5"));
+        assert!(!key_contains_token("Ctrl + F15", "F5"));
+    }
+
+    #[test]
+    fn config_size_info_counts_fixture() {
+        let _config = isolated_config();
+        let content = r#"{ "version": 1, "apps": [
+            { "name": "Editor", "keybindings": [
+                { "action": "保存", "key": "Ctrl+S" },
+                { "action": "検索", "key": "Ctrl+F" }
+            ] },
+            { "name": "Web", "keybindings": [{ "action": "新しいタブ", "key": "Ctrl+T" }] }
+        ] }"#;
+        write_config_file("keybindings.json", content);
+        let info = get_config_size_info();
+        assert_eq!(info.bytes, content.len() as u64);
+        assert_eq!(info.app_count, 2);
+        assert_eq!(info.keybinding_count, 3);
+    }
+
+    #[test]
+    fn quick_card_selects_top_rows_with_widths() {
+        let _config = isolated_config();
+        let keybindings: Vec<serde_json::Value> = (1..=10)
+            .map(|i| serde_json::json!({ "action": format!("操作{i}"), "key": format!("Ctrl+F{i}") }))
+            .collect();
+        let config = serde_json::json!({
+            "version": 1,
+            "apps": [{ "name": "Editor", "keybindings": keybindings }]
+        });
+        write_config_file("keybindings.json", &config.to_string());
+        write_config_file(
+            "usage.json",
+            r#"[{ "app": "Editor", "action": "操作10", "count": 5 }]"#,
+        );
+
+        let card = get_quick_card("Editor".to_string());
+        let actions: Vec<&str> = card.rows.iter().map(|row| row.action.as_str()).collect();
+        // よく使うものを先頭に、残りは設定順で最大 QUICK_CARD_ROWS 件
+        assert_eq!(
+            actions,
+            [
+                "操作10", "操作1", "操作2", "操作3", "操作4", "操作5", "操作6", "操作7"
+            ]
+        );
+        let range = AppSettings::default().overlay_width_range();
+        for row in &card.rows {
+            let expected = calculate_overlay_width("Editor", &row.action, &row.key, range);
+            assert!((row.width - expected).abs() < f64::EPSILON);
+        }
+        let widest = card
+            .rows
+            .iter()
+            .map(|row| row.width)
+            .fold(range.0, f64::max);
+        assert!((card.width - widest).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn launch_app_reports_missing_path() {
+        let _config = isolated_config();
+        let missing = std::env::temp_dir().join("finkey-missing-app");
+        assert_eq!(
+            launch_app(missing.to_str().unwrap()),
+            Err(format!(
+                "起動するファイルが見つかりません: {}",
+                missing.display()
+            ))
+        );
+        // 相対パスは設定フォルダを基準に解決する
+        let relative = format!("bin{}missing-app", std::path::MAIN_SEPARATOR);
+        let resolved = get_config_dir().unwrap().join(&relative);
+        assert_eq!(
+            launch_app(&relative),
+            Err(format!(
+                "起動するファイルが見つかりません: {}",
+                resolved.display()
+            ))
+        );
+    }
+
+    // シェルのコマンドとしては実行しない（macOS はアプリ名として open -a に渡す）
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn launch_app_rejects_commands() {
+        let result = launch_app("echo finkey");
+        assert!(result.unwrap_err().starts_with("起動方法（launch）には"));
+    }
+
+    #[test]
+    fn activate_app_requires_available_app_and_launch() {
+        let _config = isolated_config();
+        write_config_file(
+            "keybindings.json",
+            r#"{ "version": 1, "apps": [
+                { "name": "Editor", "bind": "finkey-test-editor", "launch": "  ", "keybindings": [] },
+                { "name": "Old", "bind": "finkey-test-old", "launch": "old", "disabled": true, "keybindings": [] }
+            ] }"#,
+        );
+        // 空の launch は設定されていない扱い
+        assert_eq!(
+            activate_app("Editor".to_string()),
+            Err(
+                "起動中のウィンドウが見つからず、起動方法（launch）も設定されていません: Editor"
+                    .to_string()
+            )
+        );
+        // 無効なアプリは対象外
+        assert_eq!(
+            activate_app("Old".to_string()),
+            Err("アプリが見つかりません: Old".to_string())
+        );
+    }
+
+    #[test]
+    fn platform_preview_excludes_other_os_apps() {
+        let config = [
+            app_config(serde_json::json!({ "os": "windows", "keybindings": [] })),
+            app_config(serde_json::json!({ "os": "macos", "keybindings": [] })),
+            app_config(serde_json::json!({ "name": "Editor", "keybindings": [] })),
+        ];
+        let names: Vec<String> = apps_for_platform(&config, &OsType::MacOS)
+            .iter()
+            .map(AppConfig::get_name)
+            .collect();
+        assert_eq!(names, ["macOS", "Editor"]);
+    }
+
+    #[test]
+    fn duplicates_are_flagged_except_for_other_titles() {
+        let apps = [
+            app_config(serde_json::json!({
+                "name": "Vim",
+                "keybindings": [
+                    { "action": "保存", "key": ":w" },
+                    { "action": "保存", "key": "Ctrl+S" },
+                    { "action": "単語移動", "key": "w", "title_contains": "- NORMAL" },
+                    { "action": "単語移動", "key": "Ctrl+Right", "title_contains": "- INSERT" }
+                ]
+            })),
+            app_config(serde_json::json!({ "name": "Vim", "keybindings": [] })),
+        ];
+        // 同じアクションでも title_contains が異なるものは重複としない
+        assert_eq!(
+            find_duplicates(&apps),
+            [
+                ConfigWarning::DuplicateAction {
+                    app: "Vim".to_string(),
+                    action: "保存".to_string()
+                },
+                ConfigWarning::DuplicateApp {
+                    app: "Vim".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn keybindings_parse_errors_report_position() {
+        let error = |name: &str, content: &str| {
+            parse_keybindings_str(Path::new(name), content).unwrap_err()
+        };
+        // 構文の誤り
+        let syntax = error("keybindings.json", "{\n  \"version\": 1,\n  \"apps\": [\n");
+        assert!(syntax.contains("line 3"), "{syntax}");
+        // 型の誤り（現在の形式と、バージョンのない配列形式）
+        let typed = error(
+            "keybindings.json",
+            "{\n  \"version\": 1,\n  \"apps\": [\n    { \"name\": 1, \"keybindings\": [] }\n  ]\n}",
+        );
+        assert!(typed.contains("line 4"), "{typed}");
+        let legacy = error("keybindings.json", "[\n  { \"keybindings\": 3 }\n]");
+        assert!(legacy.contains("line 2"), "{legacy}");
+        // YAML
+        let yaml = error(
+            "keybindings.yaml",
+            "version: 1\napps:\n  - name: Editor\n    keybindings: 3\n",
+        );
+        assert!(yaml.contains("line 4"), "{yaml}");
+    }
+
+    #[test]
+    fn imported_keybindings_replace_or_merge() {
+        let app = |name: &str| app_config(serde_json::json!({ "name": name, "keybindings": [] }));
+        let names = |config: Vec<AppConfig>| -> Vec<String> {
+            config.iter().map(AppConfig::get_name).collect()
+        };
+        let current = || vec![app("Editor"), app("Browser")];
+        let merged =
+            apply_imported_keybindings(current(), vec![app("Terminal"), app("Editor")], false);
+        assert_eq!(names(merged.unwrap()), ["Editor", "Browser", "Terminal"]);
+        let replaced = apply_imported_keybindings(current(), vec![app("Terminal")], true);
+        assert_eq!(names(replaced.unwrap()), ["Terminal"]);
+        // 空の設定での置き換えは拒否し、追加なら現在の設定のまま
+        assert!(apply_imported_keybindings(current(), Vec::new(), true).is_err());
+        let unchanged = apply_imported_keybindings(current(), Vec::new(), false);
+        assert_eq!(names(unchanged.unwrap()), ["Editor", "Browser"]);
+
+        // 取り込む内容の警告は validate_keybindings と同じ検査で求める
+        assert_eq!(
+            find_config_warnings(&[app("Editor"), app("Editor")]),
+            [ConfigWarning::DuplicateApp {
+                app: "Editor".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn key_conflicts_are_checked_for_each_os() {
+        let apps = [app_config(serde_json::json!({
+            "name": "Editor",
+            "keybindings": [
+                { "action": "保存", "key": { "windows": "Ctrl+S", "macos": "Cmd+S" } },
+                { "action": "検索", "key": { "windows": "Ctrl+F", "macos": "Cmd+S" } },
+                { "action": "閉じる", "key": "Ctrl+W" },
+                { "action": "タブを閉じる", "key": "Ctrl+W" }
+            ]
+        }))];
+        let warnings = find_key_conflicts(&apps);
+        // macOS 側のみの重複も検出し、全OS共通のキーの重複は1件にまとめる
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.contains(&ConfigWarning::KeyConflict {
+            app: "Editor".to_string(),
+            key: "Cmd+S".to_string(),
+            actions: vec!["保存".to_string(), "検索".to_string()],
+        }));
+    }
+
+    #[test]
+    fn simulate_match_uses_given_window_only() {
+        let _config = isolated_config();
+        write_config_file(
+            "keybindings.json",
+            r#"[{ "name": "Editor", "bind": ["code", "Visual Studio Code"], "keybindings": [] }]"#,
+        );
+        let names = |apps: Vec<NormalizedApp>| -> Vec<String> {
+            apps.into_iter().map(|app| app.name).collect()
+        };
+        assert_eq!(names(simulate_match(Some("Code".into()), None)), ["Editor"]);
+        assert_eq!(
+            names(simulate_match(None, Some("visual studio code".into()))),
+            ["Editor"]
+        );
+        assert!(simulate_match(Some("notepad".into()), None).is_empty());
+    }
+
+    #[test]
+    fn merge_app_configs_replaces_in_place_and_appends() {
+        let app = |name: &str, action: &str| {
+            app_config(serde_json::json!({
+                "name": name,
+                "keybindings": [{ "action": action, "key": "Ctrl+S" }]
+            }))
+        };
+        let merged = merge_app_configs(
+            vec![app("Editor", "保存"), app("Browser", "戻る")],
+            vec![app("Terminal", "コピー"), app("Editor", "上書き保存")],
+        );
+        // 同じ名前のアプリは元の位置で置き換え、新しいアプリは末尾に追加
+        let names: Vec<String> = merged.iter().map(AppConfig::get_name).collect();
+        assert_eq!(names, ["Editor", "Browser", "Terminal"]);
+        let actions: Vec<&str> = merged
+            .iter()
+            .map(|app| app.keybindings[0].action.as_str())
+            .collect();
+        assert_eq!(actions, ["上書き保存", "戻る", "コピー"]);
+    }
+
+    #[test]
+    fn merge_defaults_hides_disabled_default_apps() {
+        let _config = isolated_config();
+        let defaults = get_default_keybindings();
+        // このOSで表示される、名前が重複しないデフォルトのアプリ
+        let hidden = defaults
+            .iter()
+            .map(AppConfig::get_name)
+            .find(|name| {
+                let same_name = || defaults.iter().filter(|app| app.get_name() == *name);
+                same_name().count() == 1
+                    && same_name().all(|app| app.is_available() && !app.keybindings.is_empty())
+            })
+            .unwrap();
+        write_config_file("settings.json", r#"{ "merge_defaults": true }"#);
+        let config = serde_json::json!({
+            "version": 1,
+            "apps": [
+                { "name": hidden, "disabled": true, "keybindings": [] },
+                { "name": "Custom", "keybindings": [{ "action": "保存", "key": "Ctrl+S" }] }
+            ]
+        });
+        write_config_file("keybindings.json", &config.to_string());
+
+        // デフォルトの一覧に重ね、新しいアプリは末尾に追加
+        let effective = load_keybindings_config();
+        assert_eq!(effective.len(), defaults.len() + 1);
+        assert_eq!(effective.last().unwrap().get_name(), "Custom");
+        // "disabled": true で置き換えたデフォルトのアプリはショートカット一覧に出ない
+        let apps: Vec<String> = get_shortcuts().into_iter().map(|s| s.app).collect();
+        assert!(!apps.contains(&hidden));
+        assert!(apps.iter().any(|app| app == "Custom"));
+    }
+
+    #[test]
+    fn effective_config_is_reused_until_sources_change() {
+        let _config = isolated_config();
+        write_config_file("settings.json", r#"{ "merge_defaults": true }"#);
+        write_config_file("keybindings.json", r#"{ "version": 1, "apps": [] }"#);
+
+        // 元の設定が変わらない間は統合済みの結果をそのまま返す
+        let first = load_keybindings_config();
+        assert!(Arc::ptr_eq(&first, &load_keybindings_config()));
+
+        // keybindings.d/ にファイルが増えたら作り直す
+        let dir = get_config_dir().unwrap().join(KEYBINDINGS_DIR_NAME);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("extra.json"),
+            r#"{ "version": 1, "apps": [{ "name": "Extra", "keybindings": [] }] }"#,
+        )
+        .unwrap();
+        let second = load_keybindings_config();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(second.len(), first.len() + 1);
+        assert!(Arc::ptr_eq(&second, &load_keybindings_config()));
+    }
+
+    #[test]
+    fn keybindings_dir_reloads_changed_files() {
+        let _config = isolated_config();
+        let dir = get_config_dir().unwrap().join(KEYBINDINGS_DIR_NAME);
+        fs::create_dir_all(&dir).unwrap();
+        // 更新時刻を明示して書き込む（ファイルシステムの時刻の粒度に依存しない）
+        let write = |name: &str, app: &str, secs: u64| {
+            let path = dir.join(name);
+            let content = format!(
+                r#"{{ "version": 1, "apps": [{{ "name": "{app}", "keybindings": [] }}] }}"#
+            );
+            fs::write(&path, content).unwrap();
+            let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+        let names = || -> Vec<String> {
+            load_keybindings_dir()
+                .iter()
+                .map(AppConfig::get_name)
+                .collect()
+        };
+
+        write("a.json", "A", 1_000);
+        assert_eq!(names(), ["A"]);
+        // 一覧と更新時刻が同じ間はキャッシュを使う
+        write("a.json", "B", 1_000);
+        assert_eq!(names(), ["A"]);
+        // ファイルの更新・追加・削除はそれぞれ反映する
+        write("a.json", "B", 2_000);
+        assert_eq!(names(), ["B"]);
+        write("b.json", "C", 1_000);
+        assert_eq!(names(), ["B", "C"]);
+        fs::remove_file(dir.join("a.json")).unwrap();
+        assert_eq!(names(), ["C"]);
+    }
 }
@@ -4,11 +4,13 @@
 export const DEFAULT_APP_ICON = "\u{1F4CC}"; // 📌
 export const WINDOWS_ICON = "\u{1FA9F}"; // 🪟
 export const MACOS_ICON = "\u{1F34E}"; // 🍎
+export const LINUX_ICON = "\u{1F427}"; // 🐧
 
 // OS種別
 export const OsType = {
 	Windows: "windows",
 	Macos: "macos",
+	Linux: "linux",
 } as const;
 
 export type OsTypeValue = (typeof OsType)[keyof typeof OsType];
@@ -16,16 +18,31 @@ export type OsTypeValue = (typeof OsType)[keyof typeof OsType];
 // OS表示名
 export const WINDOWS_NAME = "Windows";
 export const MACOS_NAME = "macOS";
+export const LINUX_NAME = "Linux";
 
 // プレースホルダテキスト
 export const UNNAMED_APP = "無名のアプリ";
 
 // OS種別に応じたアイコンを取得
 export function getOsIcon(os: OsTypeValue): string {
-	return os === OsType.Windows ? WINDOWS_ICON : MACOS_ICON;
+	switch (os) {
+		case OsType.Windows:
+			return WINDOWS_ICON;
+		case OsType.Linux:
+			return LINUX_ICON;
+		default:
+			return MACOS_ICON;
+	}
 }
 
 // OS種別に応じた表示名を取得
 export function getOsName(os: OsTypeValue): string {
-	return os === OsType.Windows ? WINDOWS_NAME : MACOS_NAME;
+	switch (os) {
+		case OsType.Windows:
+			return WINDOWS_NAME;
+		case OsType.Linux:
+			return LINUX_NAME;
+		default:
+			return MACOS_NAME;
+	}
 }
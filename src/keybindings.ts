@@ -11,7 +11,13 @@ import {
 	setupSystemThemeListener,
 	setupWindowFocusListener,
 } from "./theme";
-import type { AppConfig, Keybinding, OsType as OsTypeValue } from "./types";
+import type {
+	AppConfig,
+	Keybinding,
+	KeySteps,
+	KeyValue,
+	OsType as OsTypeValue,
+} from "./types";
 
 // DOM要素
 const closeBtn = document.getElementById("close-btn") as HTMLButtonElement;
@@ -94,17 +100,34 @@ let hasChanges = false;
 let currentKeyCaptureCallback: ((key: string) => void) | null = null;
 let capturedKeys: string[] = [];
 let confirmCallback: (() => void) | null = null;
+// 実行中のOS（OS別のキーを入力し直す際の対象）
+let currentOs: OsTypeValue = OsType.Windows;
 
 // 初期化
 async function init(): Promise<void> {
 	await loadAndApplyTheme();
 	setupSystemThemeListener();
 	setupWindowFocusListener();
+	await detectCurrentOs();
 	await loadKeybindings();
 	renderAppList();
 	setupEventListeners();
 }
 
+// 実行中のOSを取得
+async function detectCurrentOs(): Promise<void> {
+	try {
+		const platform = await invoke<string>("get_platform");
+		if (platform === "darwin") {
+			currentOs = OsType.Macos;
+		} else if (platform === "linux") {
+			currentOs = OsType.Linux;
+		}
+	} catch (_e) {
+		console.log("Platform detection failed, defaulting to windows");
+	}
+}
+
 // キーバインドデータを読み込む
 async function loadKeybindings(): Promise<void> {
 	try {
@@ -409,15 +432,30 @@ function renderKeybindingsTable(config: AppConfig): void {
 
 	for (let i = 0; i < config.keybindings.length; i++) {
 		const kb = config.keybindings[i];
-		const row = createKeybindingRow(kb, i);
+		// OS固有設定はそのOS、それ以外は実行中のOSのキーを入力対象とする
+		const row = createKeybindingRow(kb, i, config.os ?? currentOs);
 		keybindingsTbody.appendChild(row);
 	}
 }
 
+// キーの表示用文字列（配列は "->" 区切り、OS別は "OS名: キー" を " / " 区切り）
+function formatKeyValue(key: KeyValue): string {
+	const formatSteps = (steps: KeySteps): string =>
+		Array.isArray(steps) ? steps.join(" -> ") : steps;
+	if (typeof key === "string" || Array.isArray(key)) {
+		return formatSteps(key);
+	}
+	return (Object.entries(key) as [OsTypeValue, KeySteps | undefined][])
+		.filter((entry): entry is [OsTypeValue, KeySteps] => entry[1] !== undefined)
+		.map(([os, steps]) => `${getOsName(os)}: ${formatSteps(steps)}`)
+		.join(" / ");
+}
+
 // キーバインド行の作成
 function createKeybindingRow(
 	kb: Keybinding,
 	index: number,
+	keyOs: OsTypeValue,
 ): HTMLTableRowElement {
 	const row = document.createElement("tr");
 
@@ -438,15 +476,19 @@ function createKeybindingRow(
 	const keyBtn = document.createElement("button");
 	keyBtn.className = "key-input-btn";
 	if (kb.key) {
-		keyBtn.textContent = kb.key;
+		keyBtn.textContent = formatKeyValue(kb.key);
 	} else {
 		keyBtn.textContent = "クリックして入力";
 		keyBtn.classList.add("placeholder");
 	}
 	keyBtn.addEventListener("click", () => {
 		openCaptureModal((key) => {
-			kb.key = key;
-			keyBtn.textContent = key;
+			// OS別のキーは対象のOSの分のみ置き換え、他のOSのキーは残す
+			kb.key =
+				typeof kb.key === "object" && !Array.isArray(kb.key)
+					? { ...kb.key, [keyOs]: key }
+					: key;
+			keyBtn.textContent = formatKeyValue(kb.key);
 			keyBtn.classList.remove("placeholder");
 			markChanged();
 		});
@@ -679,10 +721,10 @@ function addNewItem(type: "app" | "platform"): void {
 		}
 
 		// 使用可能なOSを選択
-		let os: OsTypeValue = OsType.Windows;
-		if (existingOs.has(OsType.Windows) && !existingOs.has(OsType.Macos)) {
-			os = OsType.Macos;
-		} else if (existingOs.has(OsType.Windows) && existingOs.has(OsType.Macos)) {
+		const os: OsTypeValue | undefined = Object.values(OsType).find(
+			(value) => !existingOs.has(value),
+		);
+		if (!os) {
 			alert("すべてのプラットフォーム設定が既に存在します");
 			return;
 		}
@@ -765,10 +807,14 @@ async function handleOpenFile(): Promise<void> {
 function handleReset(): void {
 	showConfirmDialog(
 		"リセットの確認",
-		"すべての設定をデフォルトに戻しますか？この操作は取り消せません。",
+		"すべての設定をデフォルトに戻しますか？現在の設定はバックアップとして保存されます。",
 		async () => {
 			try {
-				keybindings = await invoke<AppConfig[]>("reset_keybindings");
+				const backupPath = await invoke<string | null>("reset_keybindings");
+				keybindings = await invoke<AppConfig[]>("get_keybindings_raw");
+				if (backupPath) {
+					alert(`元の設定は ${backupPath} に保存しました`);
+				}
 				selectedIndex = -1;
 				hasChanges = false;
 				updateWindowTitle();
@@ -12,6 +12,7 @@ const overlayEl = document.getElementById("overlay") as HTMLElement;
 // 状態
 let countdownTimer: number | null = null;
 let remainingSeconds = 0;
+let isPinned = false;
 
 // オーバーレイペイロード
 interface OverlayPayload {
@@ -20,6 +21,8 @@ interface OverlayPayload {
 	shortcut_key: string;
 	duration: number;
 	theme: string;
+	app_color: string | null;
+	fade_ms: number;
 }
 
 // HTMLエスケープ
@@ -73,24 +76,35 @@ function startCountdown(duration: number): void {
 		remainingSeconds--;
 		updateCountdown();
 
-		if (remainingSeconds <= 0) {
-			closeOverlay();
+		// 非表示はRust側のタイマーが行う（フェードアウトも "overlay-fade-out" で開始）
+		if (remainingSeconds <= 0 && countdownTimer !== null) {
+			clearInterval(countdownTimer);
+			countdownTimer = null;
 		}
 	}, 1000);
 }
 
-// オーバーレイを閉じる
-async function closeOverlay(): Promise<void> {
-	// タイマーをクリア
-	if (countdownTimer !== null) {
-		clearInterval(countdownTimer);
-		countdownTimer = null;
+// 固定表示の状態を反映
+function applyPinned(pinned: boolean): void {
+	isPinned = pinned;
+	if (pinned) {
+		// 固定中はカウントダウンを止める（非表示は解除後にRust側のタイマーが行う）
+		if (countdownTimer !== null) {
+			clearInterval(countdownTimer);
+			countdownTimer = null;
+		}
+		countdownEl.textContent = "固定表示中";
+	} else {
+		countdownEl.textContent = "";
 	}
+}
 
+// 固定表示を切り替え
+async function togglePinned(): Promise<void> {
 	try {
-		await invoke("hide_overlay");
+		await invoke(isPinned ? "unpin_overlay" : "pin_overlay");
 	} catch (_e) {
-		console.log("Failed to hide overlay");
+		console.log("Failed to toggle overlay pin");
 	}
 }
 
@@ -139,15 +153,39 @@ async function init(): Promise<void> {
 		}
 	});
 
+	// 右クリックで固定表示を切り替え
+	overlayEl.addEventListener("contextmenu", (e) => {
+		e.preventDefault();
+		togglePinned();
+	});
+
 	// Tauriイベントリスナー
 	try {
 		await listen<OverlayPayload>("overlay-show", (event) => {
-			const { app_name, action_name, shortcut_key, duration, theme } =
-				event.payload;
+			const {
+				app_name,
+				action_name,
+				shortcut_key,
+				duration,
+				theme,
+				app_color,
+				fade_ms,
+			} = event.payload;
+
+			// 前回のフェードアウトを解除して、Rust側と同じ長さでフェードするよう設定
+			overlayEl.classList.remove("fading");
+			overlayEl.style.setProperty("--fade-ms", `${fade_ms}ms`);
 
 			// テーマを適用
 			applyThemeFromSetting(theme);
 
+			// アプリの色で枠線を表示（未設定なら枠線なし）
+			if (app_color) {
+				overlayEl.style.setProperty("--app-color", app_color);
+			} else {
+				overlayEl.style.removeProperty("--app-color");
+			}
+
 			// アプリ名とアクション名を表示
 			appNameEl.textContent = app_name;
 			actionNameEl.textContent = action_name;
@@ -155,8 +193,21 @@ async function init(): Promise<void> {
 			// ショートカットキーを表示（個別ボックス形式）
 			shortcutKeyEl.innerHTML = formatShortcutKey(shortcut_key);
 
-			// カウントダウン開始
-			startCountdown(duration);
+			// カウントダウン開始（固定表示中は開始しない）
+			if (isPinned) {
+				applyPinned(true);
+			} else {
+				startCountdown(duration);
+			}
+		});
+
+		await listen<boolean>("overlay-pinned", (event) => {
+			applyPinned(event.payload);
+		});
+
+		// 表示時間が過ぎたらフェードアウト（完了後にRust側で非表示にする）
+		await listen("overlay-fade-out", () => {
+			overlayEl.classList.add("fading");
 		});
 
 		// ウィンドウ移動イベントをリッスン
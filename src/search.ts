@@ -1,4 +1,4 @@
-import { MACOS_NAME, WINDOWS_NAME } from "./constants";
+import { getOsName, OsType } from "./constants";
 import { invoke, listen } from "./tauri-api";
 import {
 	applyTheme,
@@ -10,8 +10,12 @@ import {
 import type {
 	ActiveWindowInfo,
 	NormalizedApp,
+	OsType as OsTypeValue,
 	Platform,
+	PrimaryLabel,
+	SecondInstancePayload,
 	Shortcut,
+	WindowShownPayload,
 } from "./types";
 import { checkAndInstallUpdate } from "./updater";
 
@@ -63,6 +67,11 @@ let selectedIndex = 0;
 let filteredShortcuts: Shortcut[] = [];
 let activeWindowInfo: ActiveWindowInfo | null = null;
 let matchedApps: NormalizedApp[] = [];
+let appFilter: string | null = null;
+let escapeToHide = true;
+let primaryLabel: PrimaryLabel = "action";
+// 2つ目の起動時に渡された検索語（次の window-shown で検索欄に入れる）
+let pendingQuery: string | null = null;
 let shortcuts: Shortcut[] = [];
 
 // テーマ切り替えハンドラ
@@ -71,6 +80,50 @@ async function handleToggleTheme(): Promise<void> {
 	themeToggleBtn.title = getThemeButtonTitle();
 }
 
+// ウィンドウ表示時の処理（アクティブウィンドウ情報を受け取る）
+async function handleWindowShown(payload: WindowShownPayload): Promise<void> {
+	activeWindowInfo = payload.active_window ?? null;
+	// トレイメニューでの絞り込みは表示ごとに解除（直後の filter-app で再設定される）
+	appFilter = null;
+
+	// ウィンドウ表示時にテーマを再適用（システム設定が変わっている可能性があるため）
+	if (getCurrentThemeSetting() === "system") {
+		applyTheme();
+	}
+	themeToggleBtn.title = getThemeButtonTitle();
+
+	// ショートカットデータを再読み込み（設定ファイルが変更されている可能性があるため）
+	try {
+		shortcuts = await invoke<Shortcut[]>("get_shortcuts");
+	} catch (_e) {
+		console.log("Failed to reload shortcuts");
+	}
+
+	// Esc キーの設定は表示時点のものがバックエンドから渡される
+	escapeToHide = payload.escape_to_hide;
+
+	// マッチしたアプリはバックエンドで計算済み
+	matchedApps = payload.matched_apps;
+
+	// UIにアプリ名を表示
+	let displayText = "-";
+	if (matchedApps.length > 0) {
+		displayText = matchedApps.map((app) => app.name).join(", ");
+	} else if (activeWindowInfo) {
+		displayText = activeWindowInfo.process ?? "-";
+	}
+	activeAppNameEl.textContent = displayText;
+
+	// 状態をリセット
+	selectedIndex = 0;
+	searchInput.value = pendingQuery ?? "";
+	pendingQuery = null;
+	searchInput.focus();
+	searchInput.select();
+
+	filterAndDisplay();
+}
+
 // 初期化
 async function init(): Promise<void> {
 	// サイレントアップデートチェック（バックグラウンドで実行）
@@ -83,7 +136,13 @@ async function init(): Promise<void> {
 	// プラットフォーム検出
 	try {
 		const platform = await invoke<string>("get_platform");
-		currentPlatform = platform === "darwin" ? "mac" : "windows";
+		if (platform === "darwin") {
+			currentPlatform = "mac";
+		} else if (platform === "linux") {
+			currentPlatform = "linux";
+		} else {
+			currentPlatform = "windows";
+		}
 	} catch (_e) {
 		console.log("Platform detection failed, defaulting to mac");
 	}
@@ -96,6 +155,13 @@ async function init(): Promise<void> {
 		shortcuts = [];
 	}
 
+	// 主表示の設定を読み込む
+	try {
+		primaryLabel = await invoke<PrimaryLabel>("get_primary_label");
+	} catch (_e) {
+		console.log("Failed to load primary label, defaulting to action");
+	}
+
 	// 初期表示
 	filterAndDisplay();
 
@@ -108,47 +174,64 @@ async function init(): Promise<void> {
 
 	// Tauriイベントリスナー（アクティブウィンドウ情報を受け取る）
 	try {
-		await listen<ActiveWindowInfo | null>("window-shown", async (event) => {
-			activeWindowInfo = event.payload ?? null;
+		await listen<WindowShownPayload>("window-shown", (event) =>
+			handleWindowShown(event.payload),
+		);
+	} catch (_e) {
+		// イベントリスナー登録に失敗
+	}
 
-			// ウィンドウ表示時にテーマを再適用（システム設定が変わっている可能性があるため）
-			if (getCurrentThemeSetting() === "system") {
-				applyTheme();
-			}
-			themeToggleBtn.title = getThemeButtonTitle();
+	// 2つ目の起動時の引数を検索語として受け取る（例: `finkey 保存`）
+	// ウィンドウの表示はバックエンドが行い、直後の window-shown で反映する
+	try {
+		await listen<SecondInstancePayload>("second-instance", (event) => {
+			const query = event.payload.args
+				.filter((arg) => !arg.startsWith("-"))
+				.join(" ");
+			pendingQuery = query || null;
+		});
+	} catch (_e) {
+		// イベントリスナー登録に失敗
+	}
 
-			// ショートカットデータを再読み込み（設定ファイルが変更されている可能性があるため）
-			try {
-				shortcuts = await invoke<Shortcut[]>("get_shortcuts");
-			} catch (_e) {
-				console.log("Failed to reload shortcuts");
-			}
+	// リロードで取りこぼしたwindow-shownイベントを再同期
+	try {
+		const lastPayload = await invoke<WindowShownPayload | null>(
+			"get_last_shown_payload",
+		);
+		if (lastPayload) {
+			await handleWindowShown(lastPayload);
+		}
+	} catch (_e) {
+		console.log("Failed to get last window-shown payload");
+	}
 
-			// バックエンドでアプリをマッチング
-			try {
-				matchedApps = await invoke<NormalizedApp[]>("get_matched_apps", {
-					info: activeWindowInfo,
-				});
-			} catch (_e) {
-				console.log("Failed to get matched apps");
-				matchedApps = [];
+	// OSのテーマ変更をリッスン（テーマ設定がシステム連動の場合のみ通知される）
+	try {
+		await listen("system-theme-changed", () => {
+			if (getCurrentThemeSetting() === "system") {
+				applyTheme();
 			}
+		});
+	} catch (_e) {
+		// イベントリスナー登録に失敗
+	}
 
-			// UIにアプリ名を表示
-			let displayText = "-";
-			if (matchedApps.length > 0) {
-				displayText = matchedApps.map((app) => app.name).join(", ");
-			} else if (activeWindowInfo) {
-				displayText = activeWindowInfo.process ?? "-";
-			}
-			activeAppNameEl.textContent = displayText;
+	// 主表示の設定変更をリッスン（キーとアクションの表示順を切り替える）
+	try {
+		await listen<PrimaryLabel>("primary-label-changed", (event) => {
+			primaryLabel = event.payload;
+			displayResults();
+		});
+	} catch (_e) {
+		// イベントリスナー登録に失敗
+	}
 
-			// 状態をリセット
+	// トレイメニューで選択した検出中アプリのショートカットに絞り込む
+	try {
+		await listen<string>("filter-app", (event) => {
+			appFilter = event.payload;
 			selectedIndex = 0;
-			searchInput.value = "";
-			searchInput.focus();
-			searchInput.select();
-
 			filterAndDisplay();
 		});
 	} catch (_e) {
@@ -195,8 +278,10 @@ async function handleKeydown(e: KeyboardEvent): Promise<void> {
 			selectShortcut();
 			break;
 		case "Escape":
-			e.preventDefault();
-			hideWindow();
+			if (escapeToHide) {
+				e.preventDefault();
+				hideWindow();
+			}
 			break;
 	}
 }
@@ -217,6 +302,16 @@ async function selectShortcut(): Promise<void> {
 	} catch (e) {
 		console.log("Failed to show overlay:", e);
 	}
+
+	// 使用回数を記録（よく使うショートカットの表示用）
+	try {
+		await invoke("record_shortcut_use", {
+			action: shortcut.action,
+			app: shortcut.app,
+		});
+	} catch (e) {
+		console.log("Failed to record shortcut use:", e);
+	}
 }
 
 // ウィンドウを隠す
@@ -252,9 +347,16 @@ function filterAndDisplay(): void {
 	displayResults();
 }
 
-// OS名を取得
-function getOsName(): string {
-	return currentPlatform === "mac" ? MACOS_NAME : WINDOWS_NAME;
+// プラットフォーム種別に対応するOS種別を取得
+function getOsType(platform: Platform): OsTypeValue {
+	switch (platform) {
+		case "mac":
+			return OsType.Macos;
+		case "linux":
+			return OsType.Linux;
+		default:
+			return OsType.Windows;
+	}
 }
 
 // テキストでフィルタリングとソート
@@ -263,12 +365,16 @@ function filterByText(): void {
 
 	// 検出アプリ名のリストを取得（name で比較）
 	const detectedAppNames = matchedApps.map((app) => app.name.toLowerCase());
-	const osName = getOsName().toLowerCase();
+	const osName = getOsName(getOsType(currentPlatform)).toLowerCase();
+
+	// トレイメニューで選択したアプリに絞り込み
+	let filtered = appFilter
+		? shortcuts.filter((shortcut) => shortcut.app === appFilter)
+		: shortcuts;
 
 	// アクション名とタグでフィルタリング（クエリがある場合）
-	let filtered = shortcuts;
 	if (query) {
-		filtered = shortcuts.filter(
+		filtered = filtered.filter(
 			(shortcut) =>
 				shortcut.action.toLowerCase().includes(query) ||
 				shortcut.tags.some((tag) => tag.toLowerCase().includes(query)),
@@ -347,9 +453,10 @@ function createResultItem(shortcut: Shortcut, index: number): HTMLDivElement {
 	const item = document.createElement("div");
 	item.className = "result-item";
 	if (index === selectedIndex) item.classList.add("selected");
+	if (primaryLabel === "key") item.classList.add("key-first");
 	item.dataset.index = String(index);
 
-	const icon = shortcut.icon;
+	const iconHtml = formatIcon(shortcut);
 	const displayKey = shortcut.key;
 	const appLabel = shortcut.app;
 
@@ -361,7 +468,7 @@ function createResultItem(shortcut: Shortcut, index: number): HTMLDivElement {
 	const formattedKey = formatShortcutKey(displayKey, currentPlatform);
 
 	item.innerHTML = `
-    <div class="result-icon">${escapeHtml(icon)}</div>
+    <div class="result-icon">${iconHtml}</div>
     <div class="result-content">
       <div class="result-action">${highlightedAction}</div>
       <span class="result-category">${escapeHtml(appLabel)}</span>
@@ -380,6 +487,17 @@ function createResultItem(shortcut: Shortcut, index: number): HTMLDivElement {
 	return item;
 }
 
+// アイコンのHTMLを生成（検出アプリのアイコンを自動取得済みなら画像で表示）
+function formatIcon(shortcut: Shortcut): string {
+	const iconUrl = matchedApps.find(
+		(app) => app.name === shortcut.app,
+	)?.icon_url;
+	if (iconUrl?.startsWith("data:image/png;base64,")) {
+		return `<img class="result-icon-image" src="${escapeHtml(iconUrl)}" alt="">`;
+	}
+	return escapeHtml(shortcut.icon);
+}
+
 // テキストハイライト
 function highlightText(text: string, query: string): string {
 	if (!query) return escapeHtml(text);
@@ -9,16 +9,31 @@ export interface Shortcut {
 	action: string;
 	key: string;
 	tags: string[];
+	description?: string;
+	category?: string;
+	unbound?: boolean;
 }
 
 /** OS種別 */
 export type OsType = OsTypeValue;
 
+/** キー（文字列、または順次入力の各ステップの配列） */
+export type KeySteps = string | string[];
+
+/** キーバインドのキー（全OS共通、またはOS別） */
+export type KeyValue =
+	| KeySteps
+	| { windows?: KeySteps; macos?: KeySteps; linux?: KeySteps };
+
 /** キーバインド設定 */
 export interface Keybinding {
 	action: string;
-	key: string;
+	key: KeyValue;
 	tags?: string[];
+	title_contains?: string;
+	description?: string;
+	category?: string;
+	icon?: string;
 }
 
 /** アプリ設定（生データ） */
@@ -28,22 +43,100 @@ export interface AppConfig {
 	bind?: string | string[];
 	os?: OsType;
 	keybindings: Keybinding[];
+	auto_icon?: boolean;
+	global?: boolean;
+	color?: string;
+	url?: string;
+	fallback?: boolean;
+	disabled?: boolean;
+	match_priority?: number;
+	launch?: string;
 }
 
 /** アクティブウィンドウ情報 */
 export interface ActiveWindowInfo {
 	process?: string;
 	window?: string;
+	executable?: string;
+	owner?: ActiveWindowInfo;
+}
+
+/** window-shown イベントのペイロード */
+export interface WindowShownPayload {
+	active_window?: ActiveWindowInfo | null;
+	matched_apps: NormalizedApp[];
+	escape_to_hide: boolean;
+}
+
+/** second-instance イベントのペイロード */
+export interface SecondInstancePayload {
+	args: string[];
+	cwd: string;
 }
 
 /** 正規化されたアプリ情報（マッチしたアプリ） */
 export interface NormalizedApp {
 	name: string;
 	icon: string;
+	/** 自動取得したアイコン画像（PNGの data URL） */
+	icon_url?: string;
+	url?: string;
+}
+
+/** 設定の再読み込みで変化したショートカット */
+export interface ShortcutsDiff {
+	added: Shortcut[];
+	removed: Shortcut[];
+	changed: Shortcut[];
+}
+
+/** 設定ファイルの場所 */
+export interface ConfigPaths {
+	config_dir: string;
+	keybindings: string;
+	settings: string;
+}
+
+/** keybindings.json の検証で見つかった警告 */
+export type ConfigWarning =
+	| { kind: "duplicate_app"; app: string }
+	| { kind: "duplicate_action"; app: string; action: string }
+	| { kind: "key_conflict"; app: string; key: string; actions: string[] }
+	| { kind: "missing_os_key"; app: string; action: string; os: OsType };
+
+/** keybindings.json のサイズと項目数 */
+export interface ConfigSizeInfo {
+	bytes: number;
+	app_count: number;
+	keybinding_count: number;
+}
+
+/** クイックリファレンスカードの1行 */
+export interface QuickCardRow {
+	action: string;
+	key: string;
+	width: number;
+}
+
+/** 1つのアプリのクイックリファレンスカード */
+export interface QuickCard {
+	app: string;
+	icon: string;
+	rows: QuickCardRow[];
+	width: number;
 }
 
+/** ショートカットの主表示 */
+export type PrimaryLabel = "action" | "key";
+
+/** ショートカットの表示方法 */
+export type OverlayMode = "overlay" | "notification";
+
+/** ショートカット一覧の並び順 */
+export type ShortcutSort = "config" | "app" | "action" | "key";
+
 /** プラットフォーム種別 */
-export type Platform = "mac" | "windows";
+export type Platform = "mac" | "windows" | "linux";
 
 /** Tauri invoke コマンド名 */
 export type TauriCommand =
@@ -62,7 +155,21 @@ export type TauriCommand =
 	| "hide_overlay"
 	| "get_keybindings_raw"
 	| "save_keybindings"
-	| "reset_keybindings";
+	| "reset_keybindings"
+	| "reset_settings";
 
 /** Tauri イベント名 */
-export type TauriEvent = "window-shown" | "window-hidden" | "overlay-show";
+export type TauriEvent =
+	| "window-shown"
+	| "window-hidden"
+	| "second-instance"
+	| "overlay-show"
+	| "overlay-pinned"
+	| "overlay-fade-out"
+	| "window-pinned"
+	| "primary-label-changed"
+	| "shortcuts-diff"
+	| "system-theme-changed"
+	| "keybindings-parse-error"
+	| "keybindings-updated"
+	| "settings-updated";